@@ -138,9 +138,42 @@ impl Bitboard {
     #[inline(always)]
     pub fn pext(self, mask: Bitboard) -> usize {
         // gathers the bits specified by the mask into a contiguous lowest order chunk of the result
-        unsafe {
-            core::arch::x86_64::_pext_u64(self.0, mask.0) as usize
+        // (the instruction only where BMI2 is enabled at build time, e.g. via -C target-cpu=native)
+        #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+        return unsafe { self.bmi2_pext(mask) };
+
+        #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+        return self.software_pext(mask);
+    }
+
+    /// # Safety
+    /// The CPU has to support BMI2, see `is_x86_feature_detected!("bmi2")`.
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn bmi2_pext(self, mask: Bitboard) -> usize {
+        core::arch::x86_64::_pext_u64(self.0, mask.0) as usize
+    }
+
+    #[inline(always)]
+    pub fn software_pext(self, mask: Bitboard) -> usize {
+        // same result as the PEXT instruction, looping over the set bits of the mask only
+        let mut mask = mask.0;
+        let mut bit: usize = 1;
+        let mut out: usize = 0;
+        while mask != 0 {
+            if self.0 & mask & mask.wrapping_neg() != 0 {
+                out |= bit;
+            }
+            mask &= mask - 1;
+            bit <<= 1;
         }
+        out
+    }
+
+    #[inline(always)]
+    pub const fn magic_index(self, mask: Bitboard, magic: Bitboard, shift: u8) -> usize {
+        // index of the masked occupancy in a (fancy) magic lookup, see https://www.chessprogramming.org/Magic_Bitboards
+        ((self.0 & mask.0).wrapping_mul(magic.0) >> shift) as usize
     }
 
     /*#[inline(always)]
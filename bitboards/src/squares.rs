@@ -111,36 +111,23 @@ impl Square {
         );
     }
     pub fn from_algebraic(square: &str) -> Self {
-        // parse &str
+        Self::try_from_algebraic(square).expect("Invalid square!")
+    }
+
+    pub fn try_from_algebraic(square: &str) -> Option<Self> {
+        // parse &str, which has to consist of exactly a file and a rank char
         let mut chars = square.chars();
-        let file_char = chars.next().expect("Invalid square!");
-        let rank_char = chars.next().expect("Invalid square!");
+        let file_char = chars.next()?;
+        let rank_char = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
 
         // parse file and rank
-        let file = match file_char {
-            'a' => 0,
-            'b' => 1,
-            'c' => 2,
-            'd' => 3,
-            'e' => 4,
-            'f' => 5,
-            'g' => 6,
-            'h' => 7,
-            _ => {panic!("Invalid rank!");}
-        };
-        let rank = match rank_char {
-            '1' => 0,
-            '2' => 1,
-            '3' => 2,
-            '4' => 3,
-            '5' => 4,
-            '6' => 5,
-            '7' => 6,
-            '8' => 7,
-            _ => {panic!("Invalid file!");}
-        };
+        let file = Self::FILE_CHARS.iter().position(|&c| c == file_char)? as u8;
+        let rank = Self::RANK_CHARS.iter().position(|&c| c == rank_char)? as u8;
 
         // return square
-        Square::from_file_and_rank(file, rank)
+        return Some(Square::from_file_and_rank(file, rank));
     }
 }
\ No newline at end of file
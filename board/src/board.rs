@@ -4,6 +4,7 @@ use search::traits::AlphaBetaSearchFunctionality;
 use generic_magic::{Bool, False, True};
 
 use crate::castle_permissions::CastlePermissions;
+use crate::fen::FenError;
 use crate::pieces::Piece;
 use crate::moves::Move;
 use crate::zobrist_hash::ZobristHash;
@@ -16,6 +17,9 @@ TODO:
     - add "has_white_castling_right", ... to generic-magic
 */
 
+// the 75 move rule ends a game after 150 plies at the latest, the last count with a zobrist key is one less
+const MAX_FIFTY_MOVE_COUNTER: u8 = 149;
+
 #[derive(PartialEq, Clone)]  // TODO: Unnecessary?
 pub struct UnmakeInformation {
     pub r#move: Move,
@@ -32,6 +36,11 @@ pub struct Board {
     pub castle_permissions: CastlePermissions,
     pub en_passant_square: Option<Square>,
 
+    // squares of the castling rooks (white short, white long, black short, black long), fixed for the game
+    pub castling_rooks: [Square; 4],
+    // Chess960: castling rooks may start anywhere, castling moves are written as the king taking its rook
+    pub chess960: bool,
+
     // various bitboards
     pub white_pawns: Bitboard,
     pub white_knights: Bitboard,
@@ -57,6 +66,7 @@ pub struct Board {
     // various information to allow hashing and detection of repetition, 50 move rule, ...
     pub zobrist_hash: ZobristHash,
     pub fifty_move_counter: u8,
+    pub full_move_counter: u16,
 
     // information to undo made moves
     pub history: Vec<UnmakeInformation>
@@ -94,6 +104,31 @@ impl Board {
         }
     }
 
+    #[inline(always)]
+    pub fn castling_rook(self: &Self, of_white: bool, is_short: bool) -> Square {
+        self.castling_rooks[2 * (!of_white as usize) + (!is_short as usize)]
+    }
+
+    #[inline(always)]
+    pub fn castling_destinations(of_white: bool, is_short: bool) -> (Square, Square) {
+        // squares of king and rook after castling, the same as in standard chess
+        let rank = if of_white {0} else {7};
+        let (king_file, rook_file) = if is_short {(6, 5)} else {(2, 3)};
+        (Square::from_file_and_rank(king_file, rank), Square::from_file_and_rank(rook_file, rank))
+    }
+
+    #[inline(always)]
+    pub fn castling_squares(self: &Self, r#move: Move) -> (Square, Square, Square) {
+        // king destination, rook origin and rook destination of a castling move
+        // (works for both the king moving two files and the king taking its rook)
+        let (from_file, from_rank) = r#move.from_square().to_file_and_rank();
+        let (to_file, _) = r#move.to_square().to_file_and_rank();
+        let of_white = from_rank == 0;
+        let is_short = to_file > from_file;
+        let (king_to, rook_to) = Self::castling_destinations(of_white, is_short);
+        (king_to, self.castling_rook(of_white, is_short), rook_to)
+    }
+
     #[inline(always)]
     pub fn own_pawn<WhitesTurn: Bool>(self: &Self) -> Piece {
         if WhitesTurn::AS_BOOL {Piece::WhitePawn} else {Piece::BlackPawn}
@@ -251,6 +286,9 @@ impl Board {
             castle_permissions: CastlePermissions::empty(),
             en_passant_square: None,
 
+            castling_rooks: [Square::H1, Square::A1, Square::H8, Square::A8],
+            chess960: false,
+
             // various bitboards
             white_pawns: Bitboard(0),
             white_knights: Bitboard(0),
@@ -274,27 +312,57 @@ impl Board {
 
             zobrist_hash: ZobristHash::empty(),
             fifty_move_counter: 0,
+            full_move_counter: 1,
 
             history: Vec::new()
         }
     }
 
     pub fn from_fen(fen: &str) -> Self {
-        // build a board from the given FEN
+        // build a board from the given FEN, panics if it is invalid
+        match Self::try_from_fen(fen) {
+            Ok(board) => board,
+            Err(error) => panic!("Invalid FEN: {error}!")
+        }
+    }
 
-        let mut board = Self::empty();
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
+        // build a board from the given FEN, or report why it is not a legal position
+
+        let mut blocks = fen.split_whitespace();
+
+        let piece_block = blocks.next().ok_or(FenError::MissingField("piece placement"))?;
+        let player_block = blocks.next().ok_or(FenError::MissingField("side to move"))?;
+        let castling_block = blocks.next().ok_or(FenError::MissingField("castling"))?;
+        let en_passant_block = blocks.next().ok_or(FenError::MissingField("en-passant"))?;
+        let fifty_counter_block = blocks.next().ok_or(FenError::MissingField("50 move counter"))?;
+        let move_counter_block = blocks.next().ok_or(FenError::MissingField("move counter"))?;
+
+        let mut board = Self::try_from_fen_core(piece_block, player_block, castling_block, en_passant_block)?;
+
+        // handle counters
+        let fifty_count = fifty_counter_block.parse().map_err(|_| FenError::BadCounter)?;
+        let move_count = move_counter_block.parse().map_err(|_| FenError::BadCounter)?;
+        board.set_move_counters(fifty_count, move_count)?;
 
-        let blocks: Vec<&str> = fen.split(' ').collect();
+        return Ok(board);
+    }
+
+    pub(crate) fn try_from_fen_core(
+        piece_block: &str,
+        player_block: &str,
+        castling_block: &str,
+        en_passant_block: &str
+    ) -> Result<Self, FenError> {
+        // build a board from the first four FEN fields (shared with EPD), counters start at 0 and 1
 
-        let piece_block = *blocks.get(0).expect("Invalid FEN!");
-        let player_block = *blocks.get(1).expect("Invalid FEN!");
-        let castling_block = *blocks.get(2).expect("Invalid FEN!");
-        let en_passant_block = *blocks.get(3).expect("Invalid FEN!");
-        let fifty_counter_block = *blocks.get(4).expect("Invalid FEN!");
-        let _move_counter_block = *blocks.get(5).expect("Invalid FEN!");
+        let mut board = Self::empty();
 
         // handle pieces
         let rank_strs: Vec<&str> = piece_block.split('/').rev().collect();
+        if rank_strs.len() != 8 {
+            return Err(FenError::BadRankCount(rank_strs.len()));
+        }
 
         let mut rank: u8 = 0;
         for rank_str in rank_strs {
@@ -305,16 +373,15 @@ impl Board {
             for char in chars {
 
                 // handle char
-                if char.is_digit(10) {
-                    // if digit, decrement file and continue to next char
-                    let digit: u8 = char.to_digit(10).expect("Invalid FEN!") as u8;
-                    file += digit;
+                if let Some(digit) = char.to_digit(10) {
+                    // if digit, skip that many files and continue to next char
+                    if digit == 0 || file as u32 + digit > 8 {
+                        return Err(FenError::BadRankLength(rank + 1));
+                    }
+                    file += digit as u8;
                     continue;
                 }
 
-                // build current square
-                let square = Square::from_file_and_rank(file, rank);
-
                 // match char to Piece
                 let (piece, of_white) = match char {
                     'P' => (Piece::WhitePawn, true), 'p' => (Piece::BlackPawn, false),
@@ -323,9 +390,20 @@ impl Board {
                     'R' => (Piece::WhiteRook, true), 'r' => (Piece::BlackRook, false),
                     'Q' => (Piece::WhiteQueen, true), 'q' => (Piece::BlackQueen, false),
                     'K' => (Piece::WhiteKing, true), 'k' => (Piece::BlackKing, false),
-                    _ => panic!("Invalid FEN!")
+                    _ => return Err(FenError::UnknownPieceChar(char))
                 };
 
+                // build current square
+                if file >= 8 {
+                    return Err(FenError::BadRankLength(rank + 1));
+                }
+                let square = Square::from_file_and_rank(file, rank);
+
+                // pawns can never stand on the first or last rank
+                if (piece == Piece::WhitePawn || piece == Piece::BlackPawn) && (rank == 0 || rank == 7) {
+                    return Err(FenError::PawnOnBackRank(square));
+                }
+
                 // put piece onto board and hash it in
                 board.get_bitboard(piece).set_bit(square);
                 if of_white {
@@ -337,54 +415,79 @@ impl Board {
                 board.square_piece_mapping[square as usize] = piece;
                 board.zobrist_hash.hash_piece(piece, square);
 
-                // decrement file
+                // increment file
                 file += 1;
             }
+            if file != 8 {
+                return Err(FenError::BadRankLength(rank + 1));
+            }
 
-            // decrement rank
+            // increment rank
             rank += 1;
         }
 
+        // exactly one king per side
+        for (king, of_white) in [(board.white_king, true), (board.black_king, false)] {
+            match king.0.count_ones() {
+                0 => return Err(FenError::MissingKing(of_white)),
+                1 => {},
+                _ => return Err(FenError::TwoKings(of_white))
+            }
+        }
+
         // handle player
-        let player_char = player_block.chars().next().expect("Invalid player in FEN!");
-        match player_char {
-            'w' => {
+        match player_block {
+            "w" => {
                 board.whites_turn = true;
                 board.zobrist_hash.hash_player();
             },
-            'b' => {
+            "b" => {
                 board.whites_turn = false;
             },
-            _ => {panic!("Invalid playerin FEN!");}
-        }
-
-        // handle castling
-        let mut white_short: bool = false;
-        let mut white_long: bool = false;
-        let mut black_short: bool = false;
-        let mut black_long: bool = false;
-        for char in castling_block.chars() {
-            match char {
-                '-' => {break;},
-                'K' => {
-                    white_short = true;
-                    board.zobrist_hash.hash_white_short();
-                },
-                'Q' => {
-                    white_long = true;
-                    board.zobrist_hash.hash_white_long();
-                },
-                'k' => {
-                    black_short = true;
-                    board.zobrist_hash.hash_black_short();
-                },
-                'q' => {
-                    black_long = true;
-                    board.zobrist_hash.hash_black_long();
-                },
-                _ => {panic!("Invalid castling rights in FEN!");}
+            _ => return Err(FenError::BadSideToMove)
+        }
+
+        // handle castling, either 'KQkq' (X-FEN, meaning the outermost rook) or rook files (Shredder-FEN, 'HAha')
+        let mut rights = [false; 4];
+        if castling_block != "-" {
+            for char in castling_block.chars() {
+                let of_white = char.is_ascii_uppercase();
+                let rank = if of_white {0} else {7};
+                let rook = if of_white {Piece::WhiteRook} else {Piece::BlackRook};
+                let king_bitboard = if of_white {board.white_king} else {board.black_king};
+                let (king_file, king_rank) = Square::from_repr(king_bitboard.tzcnt() as u8).to_file_and_rank();
+                if king_rank != rank {
+                    return Err(FenError::BadCastlingField);
+                }
+
+                // find the rook the right belongs to
+                let is_rook = |file: &u8| board.piece_at(Square::from_file_and_rank(*file, rank)) == rook;
+                let rook_file = match char.to_ascii_lowercase() {
+                    'k' => (king_file + 1..8).rev().find(is_rook),
+                    'q' => (0..king_file).find(is_rook),
+                    file @ 'a'..='h' => Some(file as u8 - b'a').filter(|file| *file != king_file && is_rook(file)),
+                    _ => None
+                }.ok_or(FenError::BadCastlingField)?;
+
+                let is_short = rook_file > king_file;
+                let index = 2 * (!of_white as usize) + (!is_short as usize);
+                if rights[index] {
+                    return Err(FenError::BadCastlingField);
+                }
+                rights[index] = true;
+                board.castling_rooks[index] = Square::from_file_and_rank(rook_file, rank);
+
+                // anything but king on the e-file and rooks in the corners can only be Chess960
+                if king_file != 4 || (rook_file != 0 && rook_file != 7) {
+                    board.chess960 = true;
+                }
             }
         }
+        let [white_short, white_long, black_short, black_long] = rights;
+        if white_short {board.zobrist_hash.hash_white_short()}
+        if white_long {board.zobrist_hash.hash_white_long()}
+        if black_short {board.zobrist_hash.hash_black_short()}
+        if black_long {board.zobrist_hash.hash_black_long()}
         board.castle_permissions = CastlePermissions::new(
             white_short, white_long, black_short, black_long
         );
@@ -393,21 +496,130 @@ impl Board {
         if en_passant_block == "-" {
             board.en_passant_square = None;
         } else {
-            let square = Square::from_algebraic(en_passant_block);
+            let square = Square::try_from_algebraic(en_passant_block).ok_or(FenError::BadEnPassantSquare)?;
+
+            // the square has to lie behind a pawn of the side not to move that just advanced by two
+            let (_, rank) = square.to_file_and_rank();
+            let expected_rank = if board.whites_turn {5} else {2};
+            if rank != expected_rank {
+                return Err(FenError::EnPassantInconsistent(square));
+            }
+            let pawn = if board.whites_turn {Piece::BlackPawn} else {Piece::WhitePawn};
+            let pawn_square = square.advance_square(!board.whites_turn);
+            let start_square = square.advance_square(board.whites_turn);
+            if board.piece_at(pawn_square) != pawn
+                || board.piece_at(square) != Piece::None
+                || board.piece_at(start_square) != Piece::None {
+                return Err(FenError::EnPassantInconsistent(square));
+            }
+
             board.en_passant_square = Some(square);
             board.zobrist_hash.hash_en_passant(square);
         }
 
-        // handle 50 counter
-        let fifty_count = fifty_counter_block.parse().expect("Invalid 50 move counter in FEN!");
-        board.fifty_move_counter = fifty_count;
-        board.zobrist_hash.hash_move_count(fifty_count as usize);
+        // hash in the 50 move counter
+        board.zobrist_hash.hash_move_count(board.fifty_move_counter as usize);
+
+        // the side that just moved cannot have left its king in check
+        let (_, number_of_checkers) = if board.whites_turn {
+            board.get_checkmask_and_number_of_checkers::<False>()
+        } else {
+            board.get_checkmask_and_number_of_checkers::<True>()
+        };
+        if number_of_checkers != 0 {
+            return Err(FenError::SideNotToMoveInCheck);
+        }
+
+        return Ok(board);
+    }
 
-        /*// TODO: Add full_move_counter field to struct
-        // handle move count
-        let move_count = move_counter_block.parse().expect("Invalid move counter in FEN!");*/
+    pub(crate) fn set_move_counters(self: &mut Self, fifty_count: u8, move_count: u16) -> Result<(), FenError> {
+        // replace both counters of a freshly built board (keeping the hash up to date)
+        if fifty_count > MAX_FIFTY_MOVE_COUNTER {
+            return Err(FenError::BadCounter);
+        }
+        self.zobrist_hash.hash_move_count(self.fifty_move_counter as usize);
+        self.zobrist_hash.hash_move_count(fifty_count as usize);
+        self.fifty_move_counter = fifty_count;
+        self.full_move_counter = move_count;
+        return Ok(());
+    }
 
-        return board;
+    pub fn to_fen(self: &Self) -> String {
+        // build the FEN of the current position
+
+        let mut fen = String::with_capacity(90);
+
+        // handle pieces
+        for rank in (0u8..8).rev() {
+            let mut empty_squares: u8 = 0;
+            for file in 0u8..8 {
+                let piece = self.piece_at(Square::from_file_and_rank(file, rank));
+                if piece == Piece::None {
+                    empty_squares += 1;
+                    continue;
+                }
+                if empty_squares != 0 {
+                    fen.push(char::from(b'0' + empty_squares));
+                    empty_squares = 0;
+                }
+                fen.push(piece.to_char());
+            }
+            if empty_squares != 0 {
+                fen.push(char::from(b'0' + empty_squares));
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+
+        // handle player
+        fen.push(' ');
+        fen.push(Self::PLAYER_CHARS[self.whites_turn as usize]);
+
+        // handle castling (Chess960 uses X-FEN: the rook's file unless it is the outermost one)
+        fen.push(' ');
+        if self.castle_permissions == CastlePermissions::empty() {
+            fen.push('-');
+        } else {
+            let rights = [
+                self.castle_permissions.has_white_short(),
+                self.castle_permissions.has_white_long(),
+                self.castle_permissions.has_black_short(),
+                self.castle_permissions.has_black_long()
+            ];
+            for (index, char) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+                if !rights[index] {
+                    continue;
+                }
+                let rook_square = self.castling_rooks[index];
+                let (rook_file, rank) = rook_square.to_file_and_rank();
+                let is_short = index % 2 == 0;
+                let outer_files = if is_short {rook_file + 1..8} else {0..rook_file};
+                let is_outermost = outer_files
+                    .map(|file| self.piece_at(Square::from_file_and_rank(file, rank)))
+                    .all(|piece| piece != self.piece_at(rook_square));
+                if !self.chess960 || is_outermost {
+                    fen.push(char);
+                } else if index < 2 {
+                    fen.push(Square::FILE_CHARS[rook_file as usize].to_ascii_uppercase());
+                } else {
+                    fen.push(Square::FILE_CHARS[rook_file as usize]);
+                }
+            }
+        }
+
+        // handle en-passant square
+        fen.push(' ');
+        match self.en_passant_square {
+            None => fen.push('-'),
+            Some(square) => fen.push_str(&square.to_string())
+        }
+
+        // handle counters
+        fen.push_str(&format!(" {} {}", self.fifty_move_counter, self.full_move_counter));
+
+        return fen;
     }
 
     fn make_move_generic<
@@ -452,18 +664,18 @@ impl Board {
         // TODO: Kill this check with const-ness
         if moving_piece == self.own_rook::<WhitesTurn>() {
             if WhitesTurn::AS_BOOL {
-                if self.castle_permissions.has_white_short() && (from_square == Square::H1) {
+                if self.castle_permissions.has_white_short() && (from_square == self.castling_rooks[0]) {
                     self.castle_permissions.remove_short_rights(WhitesTurn::AS_BOOL);
                     self.zobrist_hash.hash_white_short();
-                } else if self.castle_permissions.has_white_long() && (from_square == Square::A1) {
+                } else if self.castle_permissions.has_white_long() && (from_square == self.castling_rooks[1]) {
                     self.castle_permissions.remove_long_rights(WhitesTurn::AS_BOOL);
                     self.zobrist_hash.hash_white_long();
                 }
             } else {
-                if self.castle_permissions.has_black_short() && (from_square == Square::H8) {
+                if self.castle_permissions.has_black_short() && (from_square == self.castling_rooks[2]) {
                     self.castle_permissions.remove_short_rights(WhitesTurn::AS_BOOL);
                     self.zobrist_hash.hash_black_short();
-                } else if self.castle_permissions.has_black_long() && (from_square == Square::A8) {
+                } else if self.castle_permissions.has_black_long() && (from_square == self.castling_rooks[3]) {
                     self.castle_permissions.remove_long_rights(WhitesTurn::AS_BOOL);
                     self.zobrist_hash.hash_black_long();
                 }
@@ -474,18 +686,18 @@ impl Board {
         if IsCapture::AS_BOOL {
             if r#move.captured_piece() == self.enemy_rook::<WhitesTurn>() {
                 if WhitesTurn::AS_BOOL {
-                    if self.castle_permissions.has_black_short() && (to_square == Square::H8) {
+                    if self.castle_permissions.has_black_short() && (to_square == self.castling_rooks[2]) {
                         self.castle_permissions.remove_short_rights(!WhitesTurn::AS_BOOL);
                         self.zobrist_hash.hash_black_short();
-                    } else if self.castle_permissions.has_black_long() && (to_square == Square::A8) {
+                    } else if self.castle_permissions.has_black_long() && (to_square == self.castling_rooks[3]) {
                         self.castle_permissions.remove_long_rights(!WhitesTurn::AS_BOOL);
                         self.zobrist_hash.hash_black_long();
                     }
                 } else {
-                    if self.castle_permissions.has_white_short() && (to_square == Square::H1) {
+                    if self.castle_permissions.has_white_short() && (to_square == self.castling_rooks[0]) {
                         self.castle_permissions.remove_short_rights(!WhitesTurn::AS_BOOL);
                         self.zobrist_hash.hash_white_short();
-                    } else if self.castle_permissions.has_white_long() && (to_square == Square::A1) {
+                    } else if self.castle_permissions.has_white_long() && (to_square == self.castling_rooks[1]) {
                         self.castle_permissions.remove_long_rights(!WhitesTurn::AS_BOOL);
                         self.zobrist_hash.hash_white_long();
                     }
@@ -514,29 +726,48 @@ impl Board {
         }
 
         // update bitboards
+        if IsCastling::AS_BOOL {
+            // in Chess960 king and rook may land on each others squares, so lift both before placing them
+            let (king_to, rook_from, rook_to) = self.castling_squares(r#move);
+            let rook = self.own_rook::<WhitesTurn>();
+            for (piece, square) in [(moving_piece, from_square), (rook, rook_from)] {
+                self.get_bitboard(piece).clear_bit(square);
+                self.own_mask_mut::<WhitesTurn>().clear_bit(square);
+                self.occupation.clear_bit(square);
+                self.zobrist_hash.hash_piece(piece, square);
+                self.square_piece_mapping[square as usize] = Piece::None;
+            }
+            for (piece, square) in [(moving_piece, king_to), (rook, rook_to)] {
+                self.get_bitboard(piece).set_bit(square);
+                self.own_mask_mut::<WhitesTurn>().set_bit(square);
+                self.occupation.set_bit(square);
+                self.zobrist_hash.hash_piece(piece, square);
+                self.square_piece_mapping[square as usize] = piece;
+            }
+        } else {
+            // move piece on own bitboard
+            {
+                let moving_bitboard = self.get_bitboard(moving_piece);
+                moving_bitboard.clear_bit(from_square);
+                moving_bitboard.set_bit(to_square);
+            }
 
-        // move piece on own bitboard
-        {
-            let moving_bitboard = self.get_bitboard(moving_piece);
-            moving_bitboard.clear_bit(from_square);
-            moving_bitboard.set_bit(to_square);
-        }
-
-        // move piece on own mask
-        self.own_mask_mut::<WhitesTurn>().clear_bit(from_square);
-        self.own_mask_mut::<WhitesTurn>().set_bit(to_square);
+            // move piece on own mask
+            self.own_mask_mut::<WhitesTurn>().clear_bit(from_square);
+            self.own_mask_mut::<WhitesTurn>().set_bit(to_square);
 
-        // move piece on occupation
-        self.occupation.clear_bit(from_square);
-        self.occupation.set_bit(to_square);
+            // move piece on occupation
+            self.occupation.clear_bit(from_square);
+            self.occupation.set_bit(to_square);
 
-        // move piece in hash
-        self.zobrist_hash.hash_piece(moving_piece, from_square);
-        self.zobrist_hash.hash_piece(moving_piece, to_square);
+            // move piece in hash
+            self.zobrist_hash.hash_piece(moving_piece, from_square);
+            self.zobrist_hash.hash_piece(moving_piece, to_square);
 
-        // move piece in square-piece mapping
-        self.square_piece_mapping[from_square as usize] = Piece::None;
-        self.square_piece_mapping[to_square as usize] = moving_piece;
+            // move piece in square-piece mapping
+            self.square_piece_mapping[from_square as usize] = Piece::None;
+            self.square_piece_mapping[to_square as usize] = moving_piece;
+        }
 
         // remove captured piece
         if IsCapture::AS_BOOL {
@@ -559,48 +790,6 @@ impl Board {
             }
         }
 
-        // handle rook move for castling
-        if IsCastling::AS_BOOL {
-            let (rook, (rook_from, rook_to)) = {
-                if WhitesTurn::AS_BOOL {
-                    (
-                        Piece::WhiteRook,
-                        if to_square == Square::G1 {
-                            (Square::H1, Square::F1)
-                        } else {
-                            (Square::A1, Square::D1)
-                        }
-                    )
-                } else {
-                    (
-                        Piece::BlackRook,
-                        if to_square == Square::G8 {
-                            (Square::H8, Square::F8)
-                        } else {
-                            (Square::A8, Square::D8)
-                        }
-                    )
-                }
-            } ;
-
-            let rook_bitboard = self.get_bitboard(rook);
-
-            rook_bitboard.clear_bit(rook_from);
-            rook_bitboard.set_bit(rook_to);
-
-            self.own_mask_mut::<WhitesTurn>().clear_bit(rook_from);
-            self.own_mask_mut::<WhitesTurn>().set_bit(rook_to);
-
-            self.occupation.clear_bit(rook_from);
-            self.occupation.set_bit(rook_to);
-
-            self.zobrist_hash.hash_piece(rook, rook_from);
-            self.zobrist_hash.hash_piece(rook, rook_to);
-
-            self.square_piece_mapping[rook_from as usize] = Piece::None;
-            self.square_piece_mapping[rook_to as usize] = rook;
-        }
-
         // handle promotion
         if IsPromotion::AS_BOOL {
             // remove pawn from to-square (except for own mask, occupation and square-piece-mapping)
@@ -623,14 +812,19 @@ impl Board {
             if IsCapture::AS_BOOL || (moving_piece == self.own_pawn::<WhitesTurn>()) {
                 // hash out move count, set to zero
                 self.fifty_move_counter = 0;
-            } else {
-                self.fifty_move_counter += 1;
+            } else if self.fifty_move_counter < MAX_FIFTY_MOVE_COUNTER {
+                self.fifty_move_counter += 1;  // (beyond that the game is drawn anyway)
             }
 
             // hash in new count
             self.zobrist_hash.hash_move_count(self.fifty_move_counter as usize);
         }
 
+        // a full move is completed once black moved
+        if !WhitesTurn::AS_BOOL {
+            self.full_move_counter += 1;
+        }
+
         // swap players
         self.whites_turn ^= true;
         self.zobrist_hash.hash_player()
@@ -742,6 +936,11 @@ impl Board {
         // swap players
         self.whites_turn ^= true;
 
+        // undo completion of a full move if black moved
+        if WhitesTurn::AS_BOOL {
+            self.full_move_counter -= 1;
+        }
+
         // handle promotion
         if IsPromotion::AS_BOOL {
             // add pawn onto to-square (except for own mask and occupation)
@@ -753,46 +952,25 @@ impl Board {
             self.square_piece_mapping[to_square as usize] = Piece::None;
         }
 
-        // handle rook move for castling
+        // handle castling (lift king and rook before placing them, as in make_move)
         if IsCastling::AS_BOOL {
-            let (rook, (rook_from, rook_to)) = {
-                if WhitesTurn::Not::AS_BOOL {
-                    (
-                        Piece::WhiteRook,
-                        if to_square == Square::G1 {
-                            (Square::H1, Square::F1)
-                        } else {
-                            (Square::A1, Square::D1)
-                        }
-                    )
-                } else {
-                    (
-                        Piece::BlackRook,
-                        if to_square == Square::G8 {
-                            (Square::H8, Square::F8)
-                        } else {
-                            (Square::A8, Square::D8)
-                        }
-                    )
-                }
-            } ;
-
-            let rook_bitboard = self.get_bitboard(rook);
-
-            rook_bitboard.clear_bit(rook_to);
-            rook_bitboard.set_bit(rook_from);
-
-            self.own_mask_mut::<WhitesTurn::Not>().clear_bit(rook_to);
-            self.own_mask_mut::<WhitesTurn::Not>().set_bit(rook_from);
-
-            self.occupation.clear_bit(rook_to);
-            self.occupation.set_bit(rook_from);
-
-            self.square_piece_mapping[rook_from as usize] = rook;
-            self.square_piece_mapping[rook_to as usize] = Piece::None;
+            let (king_to, rook_from, rook_to) = self.castling_squares(r#move);
+            let rook = self.own_rook::<WhitesTurn::Not>();
+            for (piece, square) in [(moving_piece, king_to), (rook, rook_to)] {
+                self.get_bitboard(piece).clear_bit(square);
+                self.own_mask_mut::<WhitesTurn::Not>().clear_bit(square);
+                self.occupation.clear_bit(square);
+                self.square_piece_mapping[square as usize] = Piece::None;
+            }
+            for (piece, square) in [(moving_piece, from_square), (rook, rook_from)] {
+                self.get_bitboard(piece).set_bit(square);
+                self.own_mask_mut::<WhitesTurn::Not>().set_bit(square);
+                self.occupation.set_bit(square);
+                self.square_piece_mapping[square as usize] = piece;
+            }
+            return;
         }
 
-
         // add captured piece
         if IsCapture::AS_BOOL {
             let captured_piece = r#move.captured_piece();
@@ -891,6 +1069,98 @@ impl Board {
         }
     }
 
+    pub fn make_null_move(self: &mut Self) {
+        /*
+        pass the turn to the opponent (for null-move pruning, must not be made in check). The 50 move
+        counter restarts, as no position before the null move can be repeated by a real game.
+        */
+
+        self.history.push(
+            UnmakeInformation {
+                r#move: Move::NULL,
+                castle_permissions: self.castle_permissions,
+                en_passant_square: self.en_passant_square,
+                fifty_move_counter: self.fifty_move_counter,
+                zobrist_hash: self.zobrist_hash
+            }
+        );
+
+        // en passant is only possible directly after the double step
+        if let Some(square) = self.en_passant_square {
+            self.zobrist_hash.hash_en_passant(square);
+            self.en_passant_square = None;
+        }
+
+        // restart 50/75 move rule counter
+        self.zobrist_hash.hash_move_count(self.fifty_move_counter as usize);
+        self.fifty_move_counter = 0;
+        self.zobrist_hash.hash_move_count(self.fifty_move_counter as usize);
+
+        // swap players
+        self.whites_turn ^= true;
+        self.zobrist_hash.hash_player();
+    }
+
+    pub fn unmake_null_move(self: &mut Self) {
+        // take back the most recent move, which has to be a null move
+
+        let info = self.history.pop().expect("No more moves to undo!");
+        debug_assert!(info.r#move.is_null(), "Most recent move is not a null move!");
+
+        self.en_passant_square = info.en_passant_square;
+        self.fifty_move_counter = info.fifty_move_counter;
+        self.zobrist_hash = info.zobrist_hash;
+        self.whites_turn ^= true;
+    }
+
+    pub fn has_non_pawn_material(self: &Self) -> bool {
+        // whether the side to move has pieces besides king and pawns (without, zugzwang is common)
+        let pieces = match self.whites_turn {
+            false => self.own_knights::<False>() | self.own_bishops::<False>() | self.own_rooks::<False>() | self.own_queens::<False>(),
+            true  => self.own_knights::<True >() | self.own_bishops::<True >() | self.own_rooks::<True >() | self.own_queens::<True >()
+        };
+        pieces.has_bits()
+    }
+
+    fn position_hash(zobrist_hash: ZobristHash, fifty_move_counter: u8) -> ZobristHash {
+        // the zobrist hash contains the 50 move counter, hash it out to compare positions only
+        let mut position_hash = zobrist_hash;
+        position_hash.hash_move_count(fifty_move_counter as usize);
+        return position_hash;
+    }
+
+    pub fn repetition_count(self: &Self) -> usize {
+        /*
+        Count how often the current position occurred before. Only positions since the last
+        irreversible move (capture or pawn move, which reset the 50 move counter) can repeat and
+        of those only every second one has the same side to move.
+        */
+
+        let current_hash = Self::position_hash(self.zobrist_hash, self.fifty_move_counter);
+        let window = usize::min(self.fifty_move_counter as usize, self.history.len());
+
+        let mut count: usize = 0;
+        for info in self.history.iter().rev().take(window).skip(1).step_by(2) {
+            if Self::position_hash(info.zobrist_hash, info.fifty_move_counter) == current_hash {
+                count += 1;
+            }
+        }
+
+        return count;
+    }
+
+    #[inline(always)]
+    pub fn is_repetition(self: &Self) -> bool {
+        // the current position occurred before (a draw for the search)
+        self.repetition_count() >= 1
+    }
+
+    #[inline(always)]
+    pub fn is_threefold_repetition(self: &Self) -> bool {
+        // the current position occurred at least twice before (a draw by the rules)
+        self.repetition_count() >= 2
+    }
+
     const PLAYER_CHARS: [char; 2] = ['b', 'w'];
     pub fn visualize(self: &Self) {
         // visualize the given board on console
@@ -947,6 +1217,8 @@ impl AlphaBetaSearchFunctionality for Board {
     type Move = Move;
     type ZobristHash = ZobristHash;
 
+    const ZOBRIST_KEY_SET: u64 = ZobristHash::KEY_SET_FINGERPRINT;
+
     #[inline(always)]
     fn is_whites_turn(self: &Self) -> bool {
         self.whites_turn
@@ -975,6 +1247,11 @@ impl AlphaBetaSearchFunctionality for Board {
         !(n_checkers == 0)
     }
 
+    fn is_draw(self: &Self) -> bool {
+        // stalemates are left to the search, as they need the legal moves anyway
+        self.is_repetition() || self.is_insufficient_material() || self.is_fifty_move_draw()
+    }
+
     #[inline(always)]
     fn zobrist_hash(self: &Self) -> Self::ZobristHash {
         self.zobrist_hash
@@ -998,7 +1275,38 @@ impl AlphaBetaSearchFunctionality for Board {
         // TODO: own move stack in SearchInfo?
         match self.history.last() {
             None => None,
+            Some(info) if info.r#move.is_null() => None,
             Some(info) => Some(info.r#move)
         }
     }
+
+    #[inline(always)]
+    fn make_null_move(self: &mut Self) {
+        self.make_null_move()
+    }
+
+    #[inline(always)]
+    fn unmake_null_move(self: &mut Self) {
+        self.unmake_null_move()
+    }
+
+    #[inline(always)]
+    fn last_move_was_null(self: &Self) -> bool {
+        self.history.last().is_some_and(|info| info.r#move.is_null())
+    }
+
+    #[inline(always)]
+    fn has_non_pawn_material(self: &Self) -> bool {
+        self.has_non_pawn_material()
+    }
+
+    #[inline(always)]
+    fn see(self: &Self, r#move: Self::Move) -> i32 {
+        self.see(r#move)
+    }
+
+    #[inline(always)]
+    fn see_ge(self: &Self, r#move: Self::Move, threshold: i32) -> bool {
+        self.see_ge(r#move, threshold)
+    }
 }
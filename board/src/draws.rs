@@ -0,0 +1,79 @@
+use bitboards::Bitboard;
+use search::traits::AlphaBetaSearchFunctionality;
+
+use crate::board::Board;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DrawReason {
+    FiftyMove,
+    InsufficientMaterial,
+    Repetition,
+    Stalemate
+}
+
+impl Board {
+    const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA55AA55AA55AA);
+
+    pub fn is_insufficient_material(self: &Self) -> bool {
+        /*
+        Check for dead positions, in which neither side can possibly mate: K vs K, KB vs K, KN vs K
+        and any number of bishops (for both sides) that all live on squares of the same color.
+        */
+
+        // pawns, rooks and queens can always mate
+        let heavy_pieces_and_pawns = self.white_pawns | self.black_pawns
+            | self.white_rooks | self.black_rooks
+            | self.white_queens | self.black_queens;
+        if heavy_pieces_and_pawns.has_bits() {
+            return false;
+        }
+
+        let knights = self.white_knights | self.black_knights;
+        let bishops = self.white_bishops | self.black_bishops;
+
+        // a single minor piece can't mate
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+
+        // with more than one minor piece only same colored bishops can't mate
+        if knights.has_bits() {
+            return false;
+        }
+        return !(bishops & Self::LIGHT_SQUARES).has_bits() || !(bishops & !Self::LIGHT_SQUARES).has_bits();
+    }
+
+    pub fn is_fifty_move_draw(self: &Self) -> bool {
+        // 50 moves (i. e. 100 plies) without capture or pawn move, unless the last one mated
+        if self.fifty_move_counter < 100 {
+            return false;
+        }
+        return !self.is_check() || !self.get_legal_moves().is_empty();
+    }
+
+    pub fn draw_reason(self: &Self) -> Option<DrawReason> {
+        // the reason why the current position is drawn by the rules (if it is)
+
+        if self.get_legal_moves().is_empty() {
+            return if self.is_check() {
+                None  // checkmate
+            } else {
+                Some(DrawReason::Stalemate)
+            }
+        }
+
+        if self.is_insufficient_material() {
+            return Some(DrawReason::InsufficientMaterial);
+        }
+
+        if self.fifty_move_counter >= 100 {
+            return Some(DrawReason::FiftyMove);
+        }
+
+        if self.is_threefold_repetition() {
+            return Some(DrawReason::Repetition);
+        }
+
+        return None;
+    }
+}
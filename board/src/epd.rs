@@ -0,0 +1,189 @@
+use std::fmt;
+
+use search::MATE_EVALUATION;
+
+use crate::board::Board;
+use crate::fen::FenError;
+use crate::moves::Move;
+use crate::san::SanError;
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EpdError {
+    BadFen(FenError),
+    MalformedOperation(String),
+    BadMove(String, SanError)
+}
+
+
+impl fmt::Display for EpdError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::BadFen(error) => write!(f, "invalid position: {error}"),
+            EpdError::MalformedOperation(opcode) => write!(f, "malformed operation \"{opcode}\""),
+            EpdError::BadMove(san, error) => write!(f, "move \"{san}\": {error}"),
+        }
+    }
+}
+
+
+#[derive(Clone, PartialEq)]
+pub struct EpdEntry {
+    pub board: Board,
+    pub operations: Vec<(String, Vec<String>)>,  // opcode and operands, in order of appearance
+    pub best_moves: Vec<Move>,   // "bm"
+    pub avoid_moves: Vec<Move>   // "am"
+}
+
+
+impl EpdEntry {
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        // parse an EPD line: the first four FEN fields followed by operations like 'bm Qg6+; id "mate.01";'
+
+        // split off the four position fields
+        let mut rest = line.trim_start();
+        let mut fields: Vec<&str> = Vec::with_capacity(4);
+        for name in ["piece placement", "side to move", "castling", "en-passant"] {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(EpdError::BadFen(FenError::MissingField(name)));
+            }
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+        let board = Board::try_from_fen_core(fields[0], fields[1], fields[2], fields[3])
+            .map_err(EpdError::BadFen)?;
+
+        // operations, each terminated by ';' (quoted operands may contain spaces and semicolons)
+        let mut operations: Vec<(String, Vec<String>)> = Vec::new();
+        let mut chars = rest.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let mut opcode = String::new();
+            while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != ';') {
+                opcode.push(c);
+            }
+
+            let mut operands: Vec<String> = Vec::new();
+            loop {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                match chars.next() {
+                    None => return Err(EpdError::MalformedOperation(opcode)),
+                    Some(';') => break,
+                    Some('"') => {
+                        let mut operand = String::new();
+                        loop {
+                            match chars.next() {
+                                None => return Err(EpdError::MalformedOperation(opcode)),
+                                Some('"') => break,
+                                Some(c) => operand.push(c)
+                            }
+                        }
+                        operands.push(operand);
+                    },
+                    Some(c) => {
+                        let mut operand = String::from(c);
+                        while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != ';') {
+                            operand.push(c);
+                        }
+                        operands.push(operand);
+                    }
+                }
+            }
+
+            if opcode.is_empty() {
+                return Err(EpdError::MalformedOperation(opcode));
+            }
+            operations.push((opcode, operands));
+        }
+
+        let mut entry = Self {
+            board,
+            operations,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new()
+        };
+
+        // counters given as operations
+        let fifty_count = entry.parse_operand("hmvc")?.unwrap_or(0);
+        let move_count = entry.parse_operand("fmvn")?.unwrap_or(1);
+        entry.board.set_move_counters(fifty_count, move_count).map_err(EpdError::BadFen)?;
+
+        // resolve the SAN moves against the position
+        entry.best_moves = entry.resolve_moves("bm")?;
+        entry.avoid_moves = entry.resolve_moves("am")?;
+
+        return Ok(entry);
+    }
+
+    pub fn read_all(epd: &str) -> Result<Vec<Self>, EpdError> {
+        // parse a suite, one position per non-empty line
+        epd.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    pub fn operands(self: &Self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn id(self: &Self) -> Option<&str> {
+        self.operands("id")?.first().map(|id| id.as_str())
+    }
+
+    pub fn direct_mate(self: &Self) -> Option<u32> {
+        // "dm": the side to move mates in this many moves
+        self.parse_operand("dm").ok().flatten()
+    }
+
+    pub fn centipawn_evaluation(self: &Self) -> Option<i32> {
+        // "ce": evaluation from the side to move's perspective
+        self.parse_operand("ce").ok().flatten()
+    }
+
+    pub fn is_solved_by(self: &Self, best_move: Move, evaluation: i32) -> bool {
+        // check a search result (evaluation from white's perspective) against bm, am and dm
+
+        if !self.best_moves.is_empty() && !self.best_moves.contains(&best_move) {
+            return false;
+        }
+        if self.avoid_moves.contains(&best_move) {
+            return false;
+        }
+        if let Some(moves) = self.direct_mate() {
+            let plies = 2 * moves as i32 - 1;
+            let sign = if self.board.whites_turn {1} else {-1};
+            if evaluation != sign * (MATE_EVALUATION - plies) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn parse_operand<T: std::str::FromStr>(self: &Self, opcode: &str) -> Result<Option<T>, EpdError> {
+        // parse the single operand of the given opcode, if present
+        return match self.operands(opcode) {
+            None => Ok(None),
+            Some([operand]) => operand.parse()
+                .map(Some)
+                .map_err(|_| EpdError::MalformedOperation(opcode.to_owned())),
+            Some(_) => Err(EpdError::MalformedOperation(opcode.to_owned()))
+        };
+    }
+
+    fn resolve_moves(self: &Self, opcode: &str) -> Result<Vec<Move>, EpdError> {
+        let mut moves = Vec::new();
+        for san in self.operands(opcode).unwrap_or(&[]) {
+            let r#move = Move::from_san(san, &self.board).map_err(|error| EpdError::BadMove(san.clone(), error))?;
+            moves.push(r#move);
+        }
+        return Ok(moves);
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+use bitboards::squares::Square;
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FenError {
+    MissingField(&'static str),
+    BadRankCount(usize),
+    BadRankLength(u8),           // rank as in the FEN, i.e. 1 to 8
+    UnknownPieceChar(char),
+    MissingKing(bool),           // of white?
+    TwoKings(bool),              // of white?
+    PawnOnBackRank(Square),
+    BadSideToMove,
+    BadCastlingField,
+    BadEnPassantSquare,
+    EnPassantInconsistent(Square),
+    BadCounter,
+    SideNotToMoveInCheck
+}
+
+
+impl FenError {
+    const COLOR_NAMES: [&'static str; 2] = ["black", "white"];
+}
+
+
+impl fmt::Display for FenError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {field} field"),
+            FenError::BadRankCount(count) => write!(f, "expected 8 ranks, found {count}"),
+            FenError::BadRankLength(rank) => write!(f, "rank {rank} does not cover exactly 8 squares"),
+            FenError::UnknownPieceChar(char) => write!(f, "unknown piece char '{char}'"),
+            FenError::MissingKing(of_white) => write!(f, "{} king is missing", Self::COLOR_NAMES[*of_white as usize]),
+            FenError::TwoKings(of_white) => write!(f, "{} has more than one king", Self::COLOR_NAMES[*of_white as usize]),
+            FenError::PawnOnBackRank(square) => write!(f, "pawn on back rank square {}", square.to_string()),
+            FenError::BadSideToMove => write!(f, "side to move has to be 'w' or 'b'"),
+            FenError::BadCastlingField => write!(f, "castling field is malformed or does not match king and rook placement"),
+            FenError::BadEnPassantSquare => write!(f, "en-passant field is neither '-' nor a square"),
+            FenError::EnPassantInconsistent(square) => write!(f, "en-passant square {} does not fit the side to move", square.to_string()),
+            FenError::BadCounter => write!(f, "move counters are malformed or out of range"),
+            FenError::SideNotToMoveInCheck => write!(f, "side not to move is in check"),
+        }
+    }
+}
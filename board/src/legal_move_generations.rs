@@ -4,16 +4,14 @@ use bitboards::{
 };
 use generic_magic::{Bool, False, True};
 use lookups::{
-    X_PEXT_MASK,
-    PLUS_PEXT_MASK,
     KNIGHT_MASK,
-    BISHOP_MASK,
-    ROOK_MASK,
     KING_MASK,
     PATH_WITHOUT_END
 };
+use lookups::sliders::{bishop_attacks, rook_attacks};
 use crate::moves::Move;
 use crate::board::Board;
+use bitboards::squares::Square;
 
 /*
 TODO:
@@ -36,8 +34,6 @@ impl Board {
         */
 
         let king_square = self.own_kings::<WhitesTurn>().tzcnt();
-        let x_pext_occupancy = self.occupation.pext(X_PEXT_MASK[king_square]);
-        let plus_pext_occupancy = self.occupation.pext(PLUS_PEXT_MASK[king_square]);
 
         let mut checkmask = Bitboard(0);
         let mut number_of_checkers = 0;
@@ -59,8 +55,8 @@ impl Board {
         - rook or plus-queen gives plus-check if it is a plus-move away from(!) the king
         - |-ed together they give a sliding checkers
         */
-        let x_checkers = BISHOP_MASK[king_square][x_pext_occupancy] & (self.enemy_bishops::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>());
-        let plus_checkers = ROOK_MASK[king_square][plus_pext_occupancy] & (self.enemy_rooks::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>());
+        let x_checkers = bishop_attacks(king_square, self.occupation) & (self.enemy_bishops::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>());
+        let plus_checkers = rook_attacks(king_square, self.occupation) & (self.enemy_rooks::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>());
         let sliding_checkers = x_checkers | plus_checkers;
         bitloop!(  // for each checking slider add the path from the slider to the king (excluding the king) onto the checkmask
             sliding_checkers, sliding_square => {
@@ -91,17 +87,14 @@ impl Board {
         Calculate pinning pieces via fancy XORs. See here:
         https://www.chessprogramming.org/X-ray_Attacks_(Bitboards)#ModifyingOccupancy
         */
-        let x_pext_mask = X_PEXT_MASK[king_square];
-        let plus_pext_mask = PLUS_PEXT_MASK[king_square];
-
-        let x_attacks = BISHOP_MASK[king_square][self.occupation.pext(x_pext_mask)];
-        let plus_attacks = ROOK_MASK[king_square][self.occupation.pext(plus_pext_mask)];
+        let x_attacks = bishop_attacks(king_square, self.occupation);
+        let plus_attacks = rook_attacks(king_square, self.occupation);
 
         let blockers = self.own_mask::<WhitesTurn>() & (x_attacks | plus_attacks);
         let non_blockers = self.occupation ^ blockers;  // remove "first level blockers" from occupation
 
-        let x_pinners_with_semi_paths = x_attacks ^ BISHOP_MASK[king_square][non_blockers.pext(x_pext_mask)];
-        let plus_pinners_with_semi_paths = plus_attacks ^ ROOK_MASK[king_square][non_blockers.pext(plus_pext_mask)];
+        let x_pinners_with_semi_paths = x_attacks ^ bishop_attacks(king_square, non_blockers);
+        let plus_pinners_with_semi_paths = plus_attacks ^ rook_attacks(king_square, non_blockers);
 
         let x_pinners = (self.enemy_bishops::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>()) & x_pinners_with_semi_paths;
         let plus_pinners = (self.enemy_rooks::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>()) & plus_pinners_with_semi_paths;
@@ -153,22 +146,22 @@ impl Board {
         // bishops
         bitloop!(
             self.enemy_bishops::<WhitesTurn>(), square => {
-                seen |= BISHOP_MASK[square as usize][occupation.pext(X_PEXT_MASK[square as usize])];
+                seen |= bishop_attacks(square as usize, occupation);
             }
         );
 
         // rooks
         bitloop!(
             self.enemy_rooks::<WhitesTurn>(), square => {
-                seen |= ROOK_MASK[square as usize][occupation.pext(PLUS_PEXT_MASK[square as usize])];
+                seen |= rook_attacks(square as usize, occupation);
             }
         );
 
         // queens
         bitloop!(
             self.enemy_queens::<WhitesTurn>(), square => {
-                seen |= BISHOP_MASK[square as usize][occupation.pext(X_PEXT_MASK[square as usize])];
-                seen |= ROOK_MASK[square as usize][occupation.pext(PLUS_PEXT_MASK[square as usize])];
+                seen |= bishop_attacks(square as usize, occupation);
+                seen |= rook_attacks(square as usize, occupation);
             }
         );
 
@@ -382,7 +375,7 @@ impl Board {
                 // find those pawns which can take en-passant by either taking left or right
                 let mut left_and_ep = if IsCheck::AS_BOOL {
                     sideways_pawns  // pawns take can take as they are not plus-pinned (see 3.)
-                        & checkmask.shift_left(WhitesTurn::AS_BOOL) // pushed pawn on checkmask? (see 2.)
+                        & checkmask.shift_right(WhitesTurn::AS_BOOL) // pushed pawn (to the left of our pawn) on checkmask? (see 2.)
                         & (
                             en_passant_bitboard & Bitboard::not_right_file(WhitesTurn::AS_BOOL)  // en-passant square that is not on the right
                         ).shift_left_pawn_attack(!WhitesTurn::AS_BOOL)
@@ -394,7 +387,7 @@ impl Board {
                 };
                 let mut right_and_ep = if IsCheck::AS_BOOL {
                     sideways_pawns  // pawns take can take as they are not plus-pinned (see 3.)
-                        & checkmask.shift_right(WhitesTurn::AS_BOOL) // pushed pawn on checkmask? (see 2.)
+                        & checkmask.shift_left(WhitesTurn::AS_BOOL) // pushed pawn (to the right of our pawn) on checkmask? (see 2.)
                         & (
                             en_passant_bitboard & Bitboard::not_left_file(WhitesTurn::AS_BOOL)  // en-passant square that is not on the right
                         ).shift_right_pawn_attack(!WhitesTurn::AS_BOOL)
@@ -427,12 +420,10 @@ impl Board {
                     let modified_occupation = self.occupation & !en_passant_bitboard.shift_backwards(WhitesTurn::AS_BOOL);
 
                     let king_square = self.own_kings::<WhitesTurn>().tzcnt();
-                    let pext_mask = PLUS_PEXT_MASK[king_square];
 
                     bitloop!(left_and_ep, square => {
                         let square_as_bitboard = !Bitboard(1 << square);
-                        let pext_occupancy = (modified_occupation & square_as_bitboard).pext(pext_mask);
-                        let seen_squares = ROOK_MASK[king_square][pext_occupancy];
+                        let seen_squares = rook_attacks(king_square, modified_occupation & square_as_bitboard);
                         if (seen_squares & enemy_plus_sliders).has_bits() {
                             left_and_ep &= square_as_bitboard;
                         }
@@ -440,8 +431,7 @@ impl Board {
 
                     bitloop!(right_and_ep, square => {
                         let square_as_bitboard = !Bitboard(1 << square);
-                        let pext_occupancy = (modified_occupation & square_as_bitboard).pext(pext_mask);
-                        let seen_squares = ROOK_MASK[king_square][pext_occupancy];
+                        let seen_squares = rook_attacks(king_square, modified_occupation & square_as_bitboard);
                         if (seen_squares & enemy_plus_sliders).has_bits() {
                             right_and_ep &= square_as_bitboard;
                         }
@@ -494,9 +484,7 @@ impl Board {
             bitloop!(
                 x_pinned_bishops, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = X_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = BISHOP_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = bishop_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares & x_pinmask;  // x-pinned bishop can only move along the pin
 
                     // register moves
@@ -513,9 +501,7 @@ impl Board {
             bitloop!(
                 unpinned_bishops, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = X_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = BISHOP_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = bishop_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares;  // unpinned bishops are free to roam
 
                     // register moves
@@ -538,9 +524,7 @@ impl Board {
             bitloop!(
                 plus_pinned_rooks, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = PLUS_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = ROOK_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = rook_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares & plus_pinmask;  // plus-pinned rooks can only move along the pin
 
                     // register moves
@@ -557,9 +541,7 @@ impl Board {
             bitloop!(
                 unpinned_rooks, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = PLUS_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = ROOK_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = rook_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares;  // unpinned rooks are free to roam
 
                     // register moves
@@ -579,9 +561,7 @@ impl Board {
             bitloop!(
                 x_pinned_queens, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = X_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = BISHOP_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = bishop_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares & x_pinmask;  // x-pinned queens can only move along that pin
 
                     // register moves
@@ -598,9 +578,7 @@ impl Board {
             bitloop!(
                 plus_pinned_queens, square => {
                     // find legal moves for bishop on <<square>>
-                    let pext_mask: Bitboard = PLUS_PEXT_MASK[square as usize];
-                    let pext_occupancy: usize = self.occupation.pext(pext_mask);
-                    let pseudo_legal_moves: Bitboard = ROOK_MASK[square as usize][pext_occupancy];
+                    let pseudo_legal_moves: Bitboard = rook_attacks(square as usize, self.occupation);
                     let legal_moves: Bitboard = pseudo_legal_moves & viable_squares & plus_pinmask;  // plus-pinned queens can only move along that pin
 
                     // register moves
@@ -617,14 +595,10 @@ impl Board {
                 unpinned_queens, square => {
 
                     // find legal moves for queen as bishop on <<square>>
-                    let x_pext_mask: Bitboard = X_PEXT_MASK[square as usize];
-                    let x_pext_occupancy: usize = self.occupation.pext(x_pext_mask);
-                    let x_pseudo_legal_moves: Bitboard = BISHOP_MASK[square as usize][x_pext_occupancy];
+                    let x_pseudo_legal_moves: Bitboard = bishop_attacks(square as usize, self.occupation);
 
                     // find legal moves for queen as rook on <<square>>
-                    let plus_pext_mask: Bitboard = PLUS_PEXT_MASK[square as usize];
-                    let plus_pext_occupancy: usize = self.occupation.pext(plus_pext_mask);
-                    let plus_pseudo_legal_moves: Bitboard = ROOK_MASK[square as usize][plus_pext_occupancy];
+                    let plus_pseudo_legal_moves: Bitboard = rook_attacks(square as usize, self.occupation);
 
                     // union the x- and plus-moves
                     let legal_moves: Bitboard = (x_pseudo_legal_moves | plus_pseudo_legal_moves) & viable_squares;  // unpinned queens are free to roam in every direction
@@ -659,35 +633,43 @@ impl Board {
                 }
             );
 
-            // castling short
-            if self.has_short_castling_rights() {
-                let must_be_empty = if WhitesTurn::AS_BOOL {Bitboard(0b01100000)} else {Bitboard(0b01100000).flip()};
-                let must_not_be_seen = if WhitesTurn::AS_BOOL {Bitboard(0b01110000)} else {Bitboard(0b01110000).flip()};
-
-                let is_empty = !(self.occupation & must_be_empty).has_bits();
-                let is_not_seen = !(seen_squares & must_not_be_seen).has_bits();
-
-                if is_empty && is_not_seen {
-                    moves.push(
-                        if WhitesTurn::AS_BOOL {Move::WHITE_SHORT_CASTLE} else {Move::BLACK_SHORT_CASTLE}
-                    );
+            // castling (in Chess960 king and rook may start anywhere on the back rank)
+            for (has_rights, is_short) in [(self.has_short_castling_rights(), true), (self.has_long_castling_rights(), false)] {
+                if !has_rights {
+                    continue;
                 }
-            }
 
-            // castling long
-            if self.has_long_castling_rights() {
-                let must_be_empty = if WhitesTurn::AS_BOOL {Bitboard(0b00001110)} else {Bitboard(0b00001110).flip()};
-                let must_not_be_seen = if WhitesTurn::AS_BOOL {Bitboard(0b00011100)} else {Bitboard(0b00011100).flip()};
+                let king_from = Square::from_repr(king_square as u8);
+                let rook_from = self.castling_rook(WhitesTurn::AS_BOOL, is_short);
+                let (king_to, rook_to) = Board::castling_destinations(WhitesTurn::AS_BOOL, is_short);
+                let king_and_rook = Bitboard((1 << king_from as u64) | (1 << rook_from as u64));
 
+                // squares the king and rook travel over (including their destinations) have to be empty but for those two
+                let king_path = back_rank_span(king_from, king_to);
+                let must_be_empty = (king_path | back_rank_span(rook_from, rook_to)) & !king_and_rook;
                 let is_empty = !(self.occupation & must_be_empty).has_bits();
-                let is_not_seen = !(seen_squares & must_not_be_seen).has_bits();
+                let is_not_seen = !(seen_squares & king_path).has_bits();
 
-                if is_empty && is_not_seen {
-                    moves.push(
-                        if WhitesTurn::AS_BOOL {Move::WHITE_LONG_CASTLE} else {Move::BLACK_LONG_CASTLE}
-                    );
+                // the castling rook might have been shielding the king's destination along the back rank
+                let is_shielded_by_rook = self.chess960 && {
+                    let occupation = self.occupation & !king_and_rook;
+                    let rank_attackers = self.enemy_rooks::<WhitesTurn>() | self.enemy_queens::<WhitesTurn>();
+                    (rook_attacks(king_to as usize, occupation) & rank_attackers).has_bits()
+                };
+
+                if is_empty && is_not_seen && !is_shielded_by_rook {
+                    // written as the king taking its rook in Chess960, as the king moving two files otherwise
+                    let to_square = if self.chess960 {rook_from} else {king_to};
+                    moves.push(Move::castling(king_from, to_square, self.own_king::<WhitesTurn>()));
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+
+fn back_rank_span(a: Square, b: Square) -> Bitboard {
+    // all squares from a to b (both included), which have to lie on the same rank
+    let (low, high) = ((a as u64).min(b as u64), (a as u64).max(b as u64));
+    return Bitboard((u64::MAX >> (63 - high)) & (u64::MAX << low));
+}
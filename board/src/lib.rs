@@ -3,15 +3,21 @@
 
 /*
 TODO:
-    - repetition, 50 moves, ...
     - recapture for move heuristic
 */
 
 pub mod board;
 mod castle_permissions;
+pub mod draws;
+pub mod epd;
+pub mod fen;
 mod legal_move_generations;
 pub mod moves;
+pub mod perft;
+pub mod pgn;
 mod pieces;
+pub mod san;
+mod see;
 mod zobrist_hash;
 mod evaluation;
 mod testing;
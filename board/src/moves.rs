@@ -56,6 +56,14 @@ impl Move {
     const IS_CAPTURE_MASK: u32 = 0x1 << Self::IS_CAPTURE_SHIFT;
     const IS_PROMOTION_MASK: u32 = 0x1 << Self::IS_PROMOTION_SHIFT;
 
+    // passing the turn (for null-move pruning), never a legal move as it goes from a1 to a1
+    pub const NULL: Self = Self(0);
+
+    #[inline(always)]
+    pub const fn is_null(self: &Self) -> bool {
+        self.0 == Self::NULL.0
+    }
+
     pub fn from_algebraic(r#move: &str, board: &Board) -> Self {
 
         // extract relevant information from str
@@ -94,11 +102,22 @@ impl Move {
         ) & (
             from_file.abs_diff(to_file) == 1
         );
-        let is_castling = (
-            (moving_piece == Piece::WhiteKing) | (moving_piece == Piece::BlackKing)
-        ) & (
-            from_file.abs_diff(to_file) == 2
+        // castling is written as the king moving two files or, in Chess960, as the king taking its own rook
+        let is_king = (moving_piece == Piece::WhiteKing) | (moving_piece == Piece::BlackKing);
+        let own_rook = if board.whites_turn {Piece::WhiteRook} else {Piece::BlackRook};
+        let is_castling = is_king & (
+            (captured_piece == own_rook) | (!board.chess960 & (from_file.abs_diff(to_file) == 2))
         );
+        if is_castling {
+            // use the notation of the board's move generation
+            let is_short = to_file > from_file;
+            let to_square = if board.chess960 {
+                board.castling_rook(board.whites_turn, is_short)
+            } else {
+                Board::castling_destinations(board.whites_turn, is_short).0
+            };
+            return Self::castling(from_square, to_square, moving_piece);
+        }
         let is_capture = board.piece_at(to_square) != Piece::None;
         let is_promotion = length != 4;
 
@@ -259,57 +278,21 @@ impl Move {
         )
     }
 
-    pub const WHITE_SHORT_CASTLE: Self = Self::from_full_info(
-        Square::E1,
-        Square::G1,
-        Piece::WhiteKing,
-        Piece::None,
-        Piece::None,
-        false,
-        false,
-        true,
-        false,
-        false
-    );
-
-    pub const BLACK_SHORT_CASTLE: Self = Self::from_full_info(
-        Square::E8,
-        Square::G8,
-        Piece::BlackKing,
-        Piece::None,
-        Piece::None,
-        false,
-        false,
-        true,
-        false,
-        false
-    );
-
-    pub const WHITE_LONG_CASTLE: Self = Self::from_full_info(
-        Square::E1,
-        Square::C1,
-        Piece::WhiteKing,
-        Piece::None,
-        Piece::None,
-        false,
-        false,
-        true,
-        false,
-        false
-    );
-
-    pub const BLACK_LONG_CASTLE: Self = Self::from_full_info(
-        Square::E8,
-        Square::C8,
-        Piece::BlackKing,
-        Piece::None,
-        Piece::None,
-        false,
-        false,
-        true,
-        false,
-        false
-    );
+    pub fn castling(king_from: Square, to: Square, king: Piece) -> Self {
+        // the to-square is the king's destination or, in Chess960, the square of the castling rook
+        Self::from_full_info(
+            king_from,
+            to,
+            king,
+            Piece::None,
+            Piece::None,
+            false,
+            false,
+            true,
+            false,
+            false
+        )
+    }
 
     #[inline(always)]
     pub fn from_square(self: &Self) -> Square {
@@ -426,4 +409,19 @@ impl SearchableMove for Move {
     fn captured_piece_as_index(self: &Self) -> usize {
         self.captured_piece() as usize
     }
-}
\ No newline at end of file
+    #[inline(always)]
+    fn to_bits(self: &Self) -> u32 {
+        self.0  // never 0, the null move isn't stored
+    }
+    #[inline(always)]
+    fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+
+impl std::fmt::Debug for Move {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Move({})", self.to_string())
+    }
+}
@@ -0,0 +1,192 @@
+/*
+Performance test (perft): count all move sequences of a given length, to check move generation
+against known results.
+
+TODO:
+    - discovered checks in PerftInfo
+    - hash table for node counts of transpositions
+*/
+
+use std::ops::AddAssign;
+
+use generic_magic::{False, True};
+
+use crate::board::Board;
+use crate::epd::EpdError;
+use crate::moves::Move;
+
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct PerftInfo {
+    // counted for the leaves, i.e. for the moves made at the last ply
+    pub nodes: usize,
+    pub captures: usize,
+    pub en_passant: usize,
+    pub castles: usize,
+    pub promotions: usize,
+    pub checks: usize,
+    pub double_checks: usize,
+    pub checkmates: usize
+}
+
+
+impl AddAssign for PerftInfo {
+    fn add_assign(self: &mut Self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+
+impl PerftInfo {
+    pub fn visualize(self: &Self) {
+        println!(
+            "\
+            \tNodes visited    : {}.\n\
+            \tThereof:\n\
+            \t- Captures       : {},\n\
+            \t- En-passants    : {},\n\
+            \t- Castles        : {},\n\
+            \t- Promotions     : {},\n\
+            \t- Checks         : {},\n\
+            \t- Double checks  : {},\n\
+            \t- Checkmates     : {}.\
+            ",
+            self.nodes, self.captures, self.en_passant, self.castles,
+            self.promotions, self.checks, self.double_checks, self.checkmates
+        )
+    }
+}
+
+
+impl Board {
+    pub fn perft(self: &mut Self, depth: u8) -> usize {
+        // number of legal move sequences of the given length
+
+        if depth == 0 {
+            return 1;
+        }
+
+        let legal_moves = self.get_legal_moves();
+
+        // no need to make the moves of the last ply
+        if depth == 1 {
+            return legal_moves.len();
+        }
+
+        let mut nodes = 0;
+        for r#move in legal_moves {
+            self.make_move(r#move);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+        return nodes;
+    }
+
+    pub fn perft_divide(self: &mut Self, depth: u8) -> Vec<(Move, usize)> {
+        // perft split by the root moves, to narrow down where move generation goes wrong
+
+        let mut divide = Vec::new();
+        if depth == 0 {
+            return divide;
+        }
+        for r#move in self.get_legal_moves() {
+            self.make_move(r#move);
+            divide.push((r#move, self.perft(depth - 1)));
+            self.unmake_move();
+        }
+        return divide;
+    }
+
+    pub fn perft_info(self: &mut Self, depth: u8) -> PerftInfo {
+        // perft with the leaves broken down by type of move and check (much slower than perft)
+
+        let mut info = PerftInfo::default();
+        if depth == 0 {
+            info.nodes = 1;
+            return info;
+        }
+
+        for r#move in self.get_legal_moves() {
+            self.make_move(r#move);
+            if depth == 1 {
+                info.nodes += 1;
+                if r#move.is_capture() {info.captures += 1}
+                if r#move.is_en_passant() {info.en_passant += 1}
+                if r#move.is_castling() {info.castles += 1}
+                if r#move.is_promotion() {info.promotions += 1}
+
+                let number_of_checkers = self.number_of_checkers();
+                if number_of_checkers != 0 {
+                    info.checks += 1;
+                    if number_of_checkers == 2 {info.double_checks += 1}
+                    if self.get_legal_moves().is_empty() {info.checkmates += 1}
+                }
+            } else {
+                info += self.perft_info(depth - 1);
+            }
+            self.unmake_move();
+        }
+        return info;
+    }
+
+    fn number_of_checkers(self: &Self) -> usize {
+        let (_, number_of_checkers) = match self.whites_turn {
+            false => self.get_checkmask_and_number_of_checkers::<False>(),
+            true  => self.get_checkmask_and_number_of_checkers::<True >()
+        };
+        return number_of_checkers;
+    }
+}
+
+
+#[derive(Clone, PartialEq)]
+pub struct PerftEntry {
+    pub board: Board,
+    pub node_counts: Vec<(u8, usize)>  // depth and expected number of nodes
+}
+
+
+impl PerftEntry {
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        // parse a line of a perft suite like "<FEN> ;D1 20 ;D2 400", the FEN with or without counters
+
+        let mut parts = line.split(';');
+
+        let fen = parts.next().unwrap_or("").trim();
+        let board = match fen.split_whitespace().collect::<Vec<&str>>()[..] {
+            [piece, player, castling, en_passant] => Board::try_from_fen_core(piece, player, castling, en_passant),
+            _ => Board::try_from_fen(fen)
+        }.map_err(EpdError::BadFen)?;
+
+        let mut node_counts = Vec::new();
+        for part in parts {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let malformed = || EpdError::MalformedOperation(part.to_owned());
+            let (depth, nodes) = part.split_once(char::is_whitespace).ok_or_else(malformed)?;
+            let depth = depth.strip_prefix('D').and_then(|depth| depth.parse().ok()).ok_or_else(malformed)?;
+            let nodes = nodes.trim().parse().map_err(|_| malformed())?;
+            node_counts.push((depth, nodes));
+        }
+
+        return Ok(Self {board, node_counts});
+    }
+
+    pub fn read_all(suite: &str) -> Result<Vec<Self>, EpdError> {
+        // parse a suite, one position per non-empty line
+        suite.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+}
+
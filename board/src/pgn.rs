@@ -0,0 +1,460 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::fen::FenError;
+use crate::moves::Move;
+use crate::san::SanError;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PgnError {
+    NoGame,
+    MalformedTag,
+    UnterminatedComment,
+    UnbalancedParenthesis,
+    MisplacedToken(String),
+    BadFen(FenError),
+    BadMove(String, SanError)
+}
+
+
+impl fmt::Display for PgnError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::NoGame => write!(f, "no game found"),
+            PgnError::MalformedTag => write!(f, "malformed tag pair"),
+            PgnError::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnError::UnbalancedParenthesis => write!(f, "unbalanced parenthesis"),
+            PgnError::MisplacedToken(token) => write!(f, "unexpected \"{token}\""),
+            PgnError::BadFen(error) => write!(f, "invalid FEN tag: {error}"),
+            PgnError::BadMove(san, error) => write!(f, "move \"{san}\": {error}"),
+        }
+    }
+}
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PgnNode {
+    pub r#move: Move,
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment_after: Option<String>,
+    pub variations: Vec<Vec<PgnNode>>  // alternatives to this move
+}
+
+
+impl PgnNode {
+    pub fn new(r#move: Move) -> Self {
+        return Self {
+            r#move,
+            nags: Vec::new(),
+            comment_before: None,
+            comment_after: None,
+            variations: Vec::new()
+        };
+    }
+}
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnNode>,  // main line
+    pub result: String
+}
+
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    San(String),
+    Result(String),
+    Open,
+    Close
+}
+
+
+impl PgnGame {
+    const STANDARD_START_FEN: &'static str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const RESULTS: [&'static str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+    const SUFFIX_ANNOTATIONS: [&'static str; 6] = ["!", "?", "!!", "??", "!?", "?!"];  // NAGs 1 to 6
+    const LINE_WIDTH: usize = 80;
+
+    pub fn new(starting_board: &Board) -> Self {
+        // an empty game with the seven tag roster, starting from the given board
+
+        let mut game = Self {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            result: "*".to_owned()
+        };
+        for (name, value) in [
+            ("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"),
+            ("White", "?"), ("Black", "?"), ("Result", "*")
+        ] {
+            game.set_tag(name, value);
+        }
+
+        // non-standard starts have to be recorded
+        let fen = starting_board.to_fen();
+        if starting_board.chess960 {
+            game.set_tag("Variant", "Chess960");
+        }
+        if fen != Self::STANDARD_START_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+
+        return game;
+    }
+
+    pub fn tag(self: &Self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(self: &mut Self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some((_, old_value)) => *old_value = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned()))
+        }
+    }
+
+    pub fn set_result(self: &mut Self, result: &str) {
+        self.result = result.to_owned();
+        self.set_tag("Result", result);
+    }
+
+    pub fn starting_board(self: &Self) -> Result<Board, PgnError> {
+        // the board the game starts from, given by the FEN tag if present
+        let mut board = match self.tag("FEN") {
+            None => Board::default(),
+            Some(fen) => Board::try_from_fen(fen).map_err(PgnError::BadFen)?
+        };
+        board.chess960 |= self.tag("Variant").is_some_and(|variant| variant.eq_ignore_ascii_case("chess960"));
+        return Ok(board);
+    }
+
+    pub fn push_move(self: &mut Self, r#move: Move) {
+        // extend the main line
+        self.moves.push(PgnNode::new(r#move));
+    }
+
+    pub fn main_line(self: &Self) -> Vec<Move> {
+        self.moves.iter().map(|node| node.r#move).collect()
+    }
+
+    pub fn final_board(self: &Self) -> Result<Board, PgnError> {
+        // replay the main line from the starting board
+        let mut board = self.starting_board()?;
+        for node in &self.moves {
+            board.make_move(node.r#move);
+        }
+        return Ok(board);
+    }
+
+    pub fn read_all(pgn: &str) -> Result<Vec<Self>, PgnError> {
+        // parse all games of a PGN collection
+
+        let tokens = Self::tokenize(pgn)?;
+        let mut games = Vec::new();
+        let mut index: usize = 0;
+
+        while index < tokens.len() {
+            let mut game = Self {
+                tags: Vec::new(),
+                moves: Vec::new(),
+                result: "*".to_owned()
+            };
+
+            // tag pairs
+            while let Some(Token::Tag(name, value)) = tokens.get(index) {
+                game.tags.push((name.clone(), value.clone()));
+                index += 1;
+            }
+
+            // movetext, replayed on the starting board
+            let mut board = game.starting_board()?;
+            game.moves = Self::parse_line(&tokens, &mut index, &mut board)?;
+            match tokens.get(index) {
+                None | Some(Token::Tag(_, _)) => {},
+                Some(Token::Result(result)) => {
+                    game.result = result.clone();
+                    index += 1;
+                },
+                Some(Token::Close) => return Err(PgnError::UnbalancedParenthesis),
+                Some(token) => return Err(PgnError::MisplacedToken(format!("{:?}", token)))
+            }
+
+            games.push(game);
+        }
+
+        return Ok(games);
+    }
+
+    pub fn read(pgn: &str) -> Result<Self, PgnError> {
+        // parse the first game of a PGN
+        let mut games = Self::read_all(pgn)?;
+        return if games.is_empty() {
+            Err(PgnError::NoGame)
+        } else {
+            Ok(games.swap_remove(0))
+        };
+    }
+
+    fn tokenize(pgn: &str) -> Result<Vec<Token>, PgnError> {
+        let mut tokens = Vec::new();
+        let mut chars = pgn.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            match char {
+                _ if char.is_whitespace() => {},
+
+                // "%" escapes the rest of the line
+                '%' => {
+                    while chars.next_if(|&c| c != '\n').is_some() {}
+                },
+
+                '[' => {
+                    // [Name "value"], with \" and \\ escaped in the value
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"' && *c != ']') {
+                        name.push(c);
+                    }
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    if name.is_empty() || chars.next() != Some('"') {
+                        return Err(PgnError::MalformedTag);
+                    }
+                    let mut value = String::new();
+                    loop {
+                        match chars.next() {
+                            None => return Err(PgnError::MalformedTag),
+                            Some('"') => break,
+                            Some('\\') => value.push(chars.next().ok_or(PgnError::MalformedTag)?),
+                            Some(c) => value.push(c)
+                        }
+                    }
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    if chars.next() != Some(']') {
+                        return Err(PgnError::MalformedTag);
+                    }
+                    tokens.push(Token::Tag(name, value));
+                },
+
+                '{' => {
+                    let mut comment = String::new();
+                    loop {
+                        match chars.next() {
+                            None => return Err(PgnError::UnterminatedComment),
+                            Some('}') => break,
+                            Some(c) => comment.push(c)
+                        }
+                    }
+                    tokens.push(Token::Comment(comment.trim().to_owned()));
+                },
+
+                ';' => {
+                    let mut comment = String::new();
+                    while let Some(c) = chars.next_if(|&c| c != '\n') {
+                        comment.push(c);
+                    }
+                    tokens.push(Token::Comment(comment.trim().to_owned()));
+                },
+
+                '(' => tokens.push(Token::Open),
+                ')' => tokens.push(Token::Close),
+
+                '$' => {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                    }
+                    let nag = digits.parse().map_err(|_| PgnError::MisplacedToken(format!("${digits}")))?;
+                    tokens.push(Token::Nag(nag));
+                },
+
+                _ => {
+                    // symbol: move number, result or SAN (possibly with suffix annotation)
+                    let mut symbol = String::from(char);
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}()[];$%".contains(*c)) {
+                        symbol.push(c);
+                    }
+
+                    if Self::RESULTS.contains(&symbol.as_str()) {
+                        tokens.push(Token::Result(symbol));
+                        continue;
+                    }
+
+                    // strip move numbers like "12." or "12..." (which may be glued to the move)
+                    let mut san = symbol.as_str();
+                    if !san.starts_with("0-0") {
+                        san = san.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.');
+                    }
+                    if san.is_empty() {
+                        continue;
+                    }
+
+                    // suffix annotations become NAGs
+                    let annotation_start = san.find(['!', '?']).unwrap_or(san.len());
+                    let (san, annotation) = san.split_at(annotation_start);
+                    tokens.push(Token::San(san.to_owned()));
+                    if !annotation.is_empty() {
+                        match Self::SUFFIX_ANNOTATIONS.iter().position(|a| *a == annotation) {
+                            None => return Err(PgnError::MisplacedToken(annotation.to_owned())),
+                            Some(index) => tokens.push(Token::Nag(index as u8 + 1))
+                        }
+                    }
+                }
+            }
+        }
+
+        return Ok(tokens);
+    }
+
+    fn parse_line(tokens: &[Token], index: &mut usize, board: &mut Board) -> Result<Vec<PgnNode>, PgnError> {
+        // parse moves (and their annotations) until the line ends, leaves the board as it was
+
+        let mut line: Vec<PgnNode> = Vec::new();
+        let mut pending_comment: Option<String> = None;
+
+        while let Some(token) = tokens.get(*index) {
+            match token {
+                Token::San(san) => {
+                    let r#move = Move::from_san(san, board).map_err(|error| PgnError::BadMove(san.clone(), error))?;
+                    let mut node = PgnNode::new(r#move);
+                    node.comment_before = pending_comment.take();
+                    line.push(node);
+                    board.make_move(r#move);
+                },
+
+                Token::Nag(nag) => match line.last_mut() {
+                    None => return Err(PgnError::MisplacedToken(format!("${nag}"))),
+                    Some(node) => node.nags.push(*nag)
+                },
+
+                Token::Comment(comment) => {
+                    let target = match line.last_mut() {
+                        Some(node) if pending_comment.is_none() => &mut node.comment_after,
+                        _ => &mut pending_comment
+                    };
+                    match target {
+                        None => *target = Some(comment.clone()),
+                        Some(existing) => {
+                            existing.push(' ');
+                            existing.push_str(comment);
+                        }
+                    }
+                },
+
+                Token::Open => {
+                    // a variation replaces the last move
+                    let last_move = match line.last() {
+                        None => return Err(PgnError::MisplacedToken("(".to_owned())),
+                        Some(node) => node.r#move
+                    };
+                    *index += 1;
+                    board.unmake_move();
+                    let variation = Self::parse_line(tokens, index, board)?;
+                    if tokens.get(*index) != Some(&Token::Close) {
+                        return Err(PgnError::UnbalancedParenthesis);
+                    }
+                    board.make_move(last_move);
+                    line.last_mut().unwrap().variations.push(variation);
+                },
+
+                Token::Close | Token::Result(_) | Token::Tag(_, _) => break
+            }
+            *index += 1;
+        }
+
+        // a trailing comment belongs to the last move
+        if let Some(comment) = pending_comment {
+            match line.last_mut() {
+                None => return Err(PgnError::MisplacedToken(format!("{{{comment}}}"))),
+                Some(node) => node.comment_after = Some(comment)
+            }
+        }
+
+        for _ in 0..line.len() {
+            board.unmake_move();
+        }
+
+        return Ok(line);
+    }
+
+    pub fn to_pgn(self: &Self) -> Result<String, PgnError> {
+        // write the game as PGN (tags, then movetext wrapped at 80 characters)
+
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(&format!("[{name} \"{value}\"]\n"));
+        }
+        pgn.push('\n');
+
+        let mut board = self.starting_board()?;
+        let mut words: Vec<String> = Vec::new();
+        Self::write_line(&self.moves, &mut board, &mut words, true);
+        words.push(self.result.clone());
+
+        let mut line_length: usize = 0;
+        for word in words {
+            if line_length != 0 && line_length + 1 + word.len() > Self::LINE_WIDTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length != 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += word.len();
+            pgn.push_str(&word);
+        }
+        pgn.push('\n');
+
+        return Ok(pgn);
+    }
+
+    fn write_line(line: &[PgnNode], board: &mut Board, words: &mut Vec<String>, mut needs_number: bool) {
+        // write moves with their annotations and variations, leaves the board as it was
+
+        for node in line {
+            if let Some(comment) = &node.comment_before {
+                words.push(format!("{{{comment}}}"));
+                needs_number = true;
+            }
+
+            // move numbers for white, and for black after an interruption
+            if board.whites_turn {
+                words.push(format!("{}. {}", board.full_move_counter, node.r#move.to_san(board)));
+            } else if needs_number {
+                words.push(format!("{}... {}", board.full_move_counter, node.r#move.to_san(board)));
+            } else {
+                words.push(node.r#move.to_san(board));
+            }
+            needs_number = false;
+
+            for nag in &node.nags {
+                words.push(format!("${nag}"));
+            }
+            if let Some(comment) = &node.comment_after {
+                words.push(format!("{{{comment}}}"));
+                needs_number = true;
+            }
+
+            // variations start from the board before the move
+            for variation in node.variations.iter().filter(|variation| !variation.is_empty()) {
+                let first_word = words.len();
+                Self::write_line(variation, board, words, true);
+                words[first_word].insert(0, '(');
+                words.last_mut().unwrap().push(')');
+                needs_number = true;
+            }
+
+            board.make_move(node.r#move);
+        }
+
+        for _ in 0..line.len() {
+            board.unmake_move();
+        }
+    }
+}
@@ -24,6 +24,11 @@ impl Piece {
         print!(" {} ", Self::PIECE_CHARS[*self as usize]);
     }
 
+    pub fn to_char(self: &Self) -> char {
+        // the FEN character of the piece
+        Self::PIECE_CHARS[*self as usize]
+    }
+
     pub fn from_repr(repr: u8) -> Self {
         if DO_ASSERTS {
             assert!(repr < 12, "Was: {}, in binary: {:b}", repr, repr);
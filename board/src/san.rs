@@ -0,0 +1,206 @@
+use std::fmt;
+
+use bitboards::squares::Square;
+use search::traits::AlphaBetaSearchFunctionality;
+
+use crate::board::Board;
+use crate::moves::Move;
+use crate::pieces::Piece;
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SanError {
+    Malformed,
+    NoSuchMove,
+    Ambiguous
+}
+
+
+impl fmt::Display for SanError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanError::Malformed => write!(f, "not a move in standard algebraic notation"),
+            SanError::NoSuchMove => write!(f, "no legal move matches"),
+            SanError::Ambiguous => write!(f, "more than one legal move matches"),
+        }
+    }
+}
+
+
+impl Move {
+    // piece letters indexed by piece type (pawns have none in SAN)
+    const SAN_PIECE_CHARS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
+    #[inline(always)]
+    fn piece_type(piece: Piece) -> usize {
+        piece as usize % 6
+    }
+
+    pub fn to_san(self: &Self, board: &Board) -> String {
+        // write the move (which has to be legal on the given board) in standard algebraic notation
+
+        let mut san = String::with_capacity(8);
+
+        let moving_type = Self::piece_type(self.moving_piece());
+        let (from_file, from_rank) = self.from_square().to_file_and_rank();
+
+        if self.is_castling() {
+            // the king moves towards the rook (also when written as king takes rook)
+            let (to_file, _) = self.to_square().to_file_and_rank();
+            san.push_str(if to_file > from_file {"O-O"} else {"O-O-O"});
+
+        } else if moving_type == 0 {
+            // pawns only name their file when capturing
+            if self.is_capture() {
+                san.push(Square::FILE_CHARS[from_file as usize]);
+                san.push('x');
+            }
+            san.push_str(&self.to_square().to_string());
+            if self.is_promotion() {
+                san.push('=');
+                san.push(Self::SAN_PIECE_CHARS[Self::piece_type(self.promoted_to())]);
+            }
+
+        } else {
+            san.push(Self::SAN_PIECE_CHARS[moving_type]);
+
+            // disambiguate by file if possible, else by rank, else by both
+            let rivals: Vec<Move> = board.get_legal_moves().into_iter()
+                .filter(|other| {
+                    other.moving_piece() == self.moving_piece()
+                        && other.to_square() == self.to_square()
+                        && other.from_square() != self.from_square()
+                })
+                .collect();
+            if !rivals.is_empty() {
+                let shares_file = rivals.iter().any(|other| other.from_square().to_file_and_rank().0 == from_file);
+                let shares_rank = rivals.iter().any(|other| other.from_square().to_file_and_rank().1 == from_rank);
+                if !shares_file {
+                    san.push(Square::FILE_CHARS[from_file as usize]);
+                } else if !shares_rank {
+                    san.push(Square::RANK_CHARS[from_rank as usize]);
+                } else {
+                    san.push(Square::FILE_CHARS[from_file as usize]);
+                    san.push(Square::RANK_CHARS[from_rank as usize]);
+                }
+            }
+
+            if self.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&self.to_square().to_string());
+        }
+
+        // check and mate suffixes
+        let mut board_after = board.clone();
+        board_after.make_move(*self);
+        if board_after.is_check() {
+            san.push(if board_after.get_legal_moves().is_empty() {'#'} else {'+'});
+        }
+
+        return san;
+    }
+
+    pub fn from_san(san: &str, board: &Board) -> Result<Self, SanError> {
+        // find the legal move on the given board that the SAN describes
+
+        // strip check, mate and annotation suffixes
+        let mut san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        san = san.strip_suffix("e.p.").unwrap_or(san).trim_end();
+
+        let legal_moves = board.get_legal_moves();
+
+        // castling (also accepting zeros for the letter O)
+        let castling_is_short = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None
+        };
+        if let Some(is_short) = castling_is_short {
+            return legal_moves.into_iter()
+                .find(|r#move| {
+                    let (from_file, _) = r#move.from_square().to_file_and_rank();
+                    let (to_file, _) = r#move.to_square().to_file_and_rank();
+                    r#move.is_castling() && (to_file > from_file) == is_short
+                })
+                .ok_or(SanError::NoSuchMove);
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+
+        // moving piece type (pawns have no letter)
+        let moving_type = match chars.first() {
+            None => return Err(SanError::Malformed),
+            Some(char) => match Self::SAN_PIECE_CHARS[1..].iter().position(|c| c == char) {
+                None => 0,
+                Some(index) => {
+                    chars.remove(0);
+                    index + 1
+                }
+            }
+        };
+
+        // promotion, with or without '='
+        let mut promotion_type: Option<usize> = None;
+        if let Some(char) = chars.last() {
+            if let Some(index) = Self::SAN_PIECE_CHARS[1..5].iter().position(|c| c == char) {
+                promotion_type = Some(index + 1);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        // target square
+        if chars.len() < 2 {
+            return Err(SanError::Malformed);
+        }
+        let to_str: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let to_square = Square::try_from_algebraic(&to_str).ok_or(SanError::Malformed)?;
+
+        // capture marker and (partial) origin square
+        let is_capture = chars.last() == Some(&'x');
+        if is_capture {
+            chars.pop();
+        }
+        let mut from_file: Option<u8> = None;
+        let mut from_rank: Option<u8> = None;
+        for char in chars {
+            if let Some(file) = Square::FILE_CHARS.iter().position(|&c| c == char) {
+                if from_file.is_some() || from_rank.is_some() {
+                    return Err(SanError::Malformed);
+                }
+                from_file = Some(file as u8);
+            } else if let Some(rank) = Square::RANK_CHARS.iter().position(|&c| c == char) {
+                if from_rank.is_some() {
+                    return Err(SanError::Malformed);
+                }
+                from_rank = Some(rank as u8);
+            } else {
+                return Err(SanError::Malformed);
+            }
+        }
+
+        // match against the legal moves
+        let mut candidates = legal_moves.into_iter().filter(|r#move| {
+            let (file, rank) = r#move.from_square().to_file_and_rank();
+            !r#move.is_castling()
+                && Self::piece_type(r#move.moving_piece()) == moving_type
+                && r#move.to_square() == to_square
+                && (r#move.is_capture() || !is_capture)
+                && from_file.is_none_or(|f| f == file)
+                && from_rank.is_none_or(|r| r == rank)
+                && match promotion_type {
+                    None => !r#move.is_promotion(),
+                    Some(promotion_type) => r#move.is_promotion()
+                        && Self::piece_type(r#move.promoted_to()) == promotion_type
+                }
+        });
+        return match (candidates.next(), candidates.next()) {
+            (None, _) => Err(SanError::NoSuchMove),
+            (Some(r#move), None) => Ok(r#move),
+            (Some(_), Some(_)) => Err(SanError::Ambiguous)
+        };
+    }
+}
@@ -0,0 +1,140 @@
+use bitboards::Bitboard;
+use bitboards::squares::Square;
+use lookups::{KING_MASK, KNIGHT_MASK};
+use lookups::sliders::{bishop_attacks, rook_attacks};
+
+use crate::board::Board;
+use crate::moves::Move;
+
+/*
+TODO:
+    - pinned pieces take part in exchanges
+    - a pawn recapturing on the last rank doesn't promote
+*/
+
+static SEE_VALUES: [i32; 6] = [100, 300, 325, 500, 900, 20_000];
+
+
+impl Board {
+    fn attackers_to(self: &Self, square: usize, occupation: Bitboard) -> Bitboard {
+        // pieces of both sides attacking the square, sliders see through everything not in occupation
+        let target = Bitboard(1 << square);
+
+        // pawns attack the square if they are a pawn move away from(!) it
+        let white_pawns = self.white_pawns & (
+            target.shift_left_pawn_attack(false) & Bitboard::not_left_file(true)
+                | target.shift_right_pawn_attack(false) & Bitboard::not_right_file(true)
+        );
+        let black_pawns = self.black_pawns & (
+            target.shift_left_pawn_attack(true) & Bitboard::not_left_file(false)
+                | target.shift_right_pawn_attack(true) & Bitboard::not_right_file(false)
+        );
+
+        let knights = KNIGHT_MASK[square] & (self.white_knights | self.black_knights);
+        let kings = KING_MASK[square] & (self.white_king | self.black_king);
+        let x_sliders = bishop_attacks(square, occupation)
+            & (self.white_bishops | self.black_bishops | self.white_queens | self.black_queens);
+        let plus_sliders = rook_attacks(square, occupation)
+            & (self.white_rooks | self.black_rooks | self.white_queens | self.black_queens);
+
+        return (white_pawns | black_pawns | knights | kings | x_sliders | plus_sliders) & occupation;
+    }
+
+    fn least_valuable_attacker(self: &Self, attackers: Bitboard, of_white: bool) -> Option<(usize, i32)> {
+        // square and value of the cheapest piece of the given side among the attackers
+        let pieces = if of_white {
+            [self.white_pawns, self.white_knights, self.white_bishops, self.white_rooks, self.white_queens, self.white_king]
+        } else {
+            [self.black_pawns, self.black_knights, self.black_bishops, self.black_rooks, self.black_queens, self.black_king]
+        };
+        for (index, bitboard) in pieces.into_iter().enumerate() {
+            let candidates = bitboard & attackers;
+            if candidates.has_bits() {
+                return Some((candidates.tzcnt(), SEE_VALUES[index]));
+            }
+        }
+        return None;
+    }
+
+    pub fn see(self: &Self, r#move: Move) -> i32 {
+        /*
+        Static exchange evaluation: the material the side to move wins (in centipawns) if both sides
+        keep recapturing on the to-square of the move with their least valuable attacker, each side
+        free to stop whenever continuing would lose material. Sliders behind the capturing pieces
+        join in as x-rays. For details see: https://www.chessprogramming.org/SEE_-_The_Swap_Algorithm
+        */
+
+        if r#move.is_castling() {
+            return 0;
+        }
+
+        let to_square = r#move.to_square() as usize;
+        let mut occupation = self.occupation;
+        occupation.clear_bit(r#move.from_square());
+
+        // gains[depth] is the material balance for the side making the depth-th capture
+        let mut gains = [0i32; 32];
+        if r#move.is_en_passant() {
+            gains[0] = SEE_VALUES[0];
+            occupation.clear_bit(r#move.to_square().advance_square(!self.whites_turn));
+        } else if r#move.is_capture() {
+            gains[0] = SEE_VALUES[r#move.captured_piece() as usize % 6];
+        }
+        let mut value_on_square = SEE_VALUES[r#move.moving_piece() as usize % 6];
+        if r#move.is_promotion() {
+            value_on_square = SEE_VALUES[r#move.promoted_to() as usize % 6];
+            gains[0] += value_on_square - SEE_VALUES[0];
+        }
+
+        let mut depth: usize = 0;
+        let mut of_white = !self.whites_turn;
+        while depth + 1 < gains.len() {
+            depth += 1;
+
+            // what the side to capture next gains, if it does capture
+            gains[depth] = value_on_square - gains[depth - 1];
+            if i32::max(-gains[depth - 1], gains[depth]) < 0 {
+                break;  // whoever is to capture is already worse off, stopping wins for the other side
+            }
+
+            let attackers = self.attackers_to(to_square, occupation);
+            let Some((square, value)) = self.least_valuable_attacker(attackers, of_white) else {
+                break;
+            };
+            occupation.clear_bit(Square::from_repr(square as u8));
+            value_on_square = value;
+            of_white = !of_white;
+        }
+
+        // let every side decide whether capturing pays, from the last capture backwards
+        while depth > 1 {
+            depth -= 1;
+            gains[depth - 1] = -i32::max(-gains[depth - 1], gains[depth]);
+        }
+
+        return gains[0];
+    }
+
+    pub fn see_ge(self: &Self, r#move: Move, threshold: i32) -> bool {
+        // whether the static exchange evaluation of the move is at least the threshold
+
+        // the exchange can neither win more than the first capture, nor lose more than the moving piece
+        let captured_value = if r#move.is_en_passant() {
+            SEE_VALUES[0]
+        } else if r#move.is_capture() {
+            SEE_VALUES[r#move.captured_piece() as usize % 6]
+        } else {
+            0
+        };
+        if !r#move.is_promotion() {
+            if captured_value < threshold {
+                return false;
+            }
+            if captured_value - SEE_VALUES[r#move.moving_piece() as usize % 6] >= threshold {
+                return true;
+            }
+        }
+
+        return self.see(r#move) >= threshold;
+    }
+}
@@ -1,10 +1,14 @@
 #![allow(dead_code)]
 
-use std::time::{Duration, Instant};
-
+use bitboards::Bitboard;
+use bitboards::squares::Square;
 use generic_magic::{Bool, False};
 
 use crate::board::Board;
+use crate::draws::DrawReason;
+use crate::fen::FenError;
+use crate::perft::PerftInfo;
+use crate::san::SanError;
 
 
 static PERFT_FENS: &[&str] = &[
@@ -14,30 +18,178 @@ static PERFT_FENS: &[&str] = &[
     "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",  // Perft: done to depth 6, MU-Test: ?
     "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",  // Perft: ?, MU-Test: ?
     "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",  // Perft: ?, MU-Test: ?
+    "8/8/8/2k5/2pP4/8/B7/4K3 b - d3 0 3",  // the double pushed pawn gives check, taking it en-passant evades
+    "4k3/b7/8/2Pp4/2K5/8/8/8 w - d6 0 3",  // (the same for white)
+];
+
+// positions in check by a double pushed pawn, with the en-passant capture evading it
+static EN_PASSANT_EVASION_FENS: &[(&str, &str)] = &[
+    ("8/8/8/2k5/2pP4/8/B7/4K3 b - d3 0 3", "c4d3"),
+    ("4k3/b7/8/2Pp4/2K5/8/8/8 w - d6 0 3", "c5d6"),
+];
+
+// positions the search gets through quickly enough to compare windows
+static ASPIRATION_FENS: &[&str] = &[
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+];
+
+static PERFT_EPD: &str = include_str!("../suites/perft.epd");
+
+// node counts broken down by type for some of the perft positions (depth, info)
+static PERFT_INFOS: &[(&str, u8, PerftInfo)] = &[
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 4, PerftInfo {
+        nodes: 197281, captures: 1576, en_passant: 0, castles: 0, promotions: 0, checks: 469, double_checks: 0, checkmates: 8
+    }),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 3, PerftInfo {
+        nodes: 97862, captures: 17102, en_passant: 45, castles: 3162, promotions: 0, checks: 993, double_checks: 0, checkmates: 1
+    }),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, PerftInfo {
+        nodes: 43238, captures: 3348, en_passant: 123, castles: 0, promotions: 0, checks: 1680, double_checks: 0, checkmates: 17
+    }),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3, PerftInfo {
+        nodes: 9467, captures: 1021, en_passant: 4, castles: 0, promotions: 120, checks: 38, double_checks: 0, checkmates: 22
+    }),
+];
+
+// castling moves in Chess960 notation (king takes rook) and their SAN, None if the castling is illegal
+static CHESS960_CASTLES: &[(&str, &str, Option<&str>)] = &[
+    ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", "f1g1", Some("O-O")),  // king and rook swap squares
+    ("6k1/8/8/8/8/8/8/rRK4R w HB - 0 1", "c1h1", Some("O-O")),
+    ("6k1/8/8/8/8/8/8/rRK4R w HB - 0 1", "c1b1", None),  // the rook shields the king's destination
+    ("1r2k1r1/8/8/8/8/8/8/1R2K1R1 b BGbg - 0 1", "e8b8", Some("O-O-O")),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1", Some("O-O")),  // standard position with UCI_Chess960
+];
+
+static MATE_EPD: &str = include_str!("../suites/mate.epd");
+
+static PERPETUAL_FENS: &[(&str, &str, i32)] = &[
+    // the side to move is lost but can force a perpetual check
+    ("8/6pk/8/8/8/8/1qr2PPP/3Q2K1 w - - 0 1", "d1h5", 0),  // Qh5+, Kg8, Qe8+, Kh7, Qh5+
+    ("3q2k1/1QR2ppp/8/8/8/8/6PK/8 b - - 0 1", "d8h4", 0),  // Qh4+, Kg1, Qe1+, Kh2, Qh4+
+];
+
+static DRAW_FENS: &[(&str, Option<DrawReason>)] = &[
+    // stalemates (and a mate for contrast)
+    ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Some(DrawReason::Stalemate)),
+    ("k7/8/1QK5/8/8/8/8/8 b - - 0 1", Some(DrawReason::Stalemate)),
+    ("6Qk/8/6K1/8/8/8/8/8 b - - 0 1", None),
+
+    // dead positions
+    ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", Some(DrawReason::InsufficientMaterial)),  // K vs K
+    ("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1", Some(DrawReason::InsufficientMaterial)),  // KB vs K
+    ("8/8/4k3/8/8/3K4/8/6n1 w - - 0 1", Some(DrawReason::InsufficientMaterial)),  // K vs KN
+    ("8/8/3bk3/8/8/3K4/8/2B1B3 w - - 0 1", Some(DrawReason::InsufficientMaterial)),  // same colored bishops
+    ("8/8/4k3/8/8/3K4/8/4BB2 w - - 0 1", None),  // bishop pair
+    ("8/8/4k3/8/8/3K4/8/4NB2 w - - 0 1", None),  // bishop and knight
+    ("8/8/4k3/8/8/3K4/8/4NN2 w - - 0 1", None),  // two knights (a mate is possible)
+    ("8/8/4k3/8/8/3K4/8/5b1N w - - 0 1", None),  // knight vs bishop (a mate is possible)
+    ("8/8/4k3/8/8/3K4/4P3/8 w - - 0 1", None),  // K vs KP
+
+    // 50 move rule (unless the 100th ply mates)
+    ("8/8/4k3/8/8/3K4/8/R7 b - - 99 80", None),
+    ("8/8/4k3/8/8/3K4/8/R7 b - - 100 80", Some(DrawReason::FiftyMove)),
+    ("8/8/4k3/8/8/3K4/8/R7 b - - 149 105", Some(DrawReason::FiftyMove)),  // nobody claimed the draw
+    ("R6k/8/6K1/8/8/8/8/8 b - - 100 80", None),
 ];
 
-static MATE_FENS: &[(&str, &str, i32)] = &[
-    // in 5 plies
-    ("r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1", "f6a6", 30_000 - 5),  // Ra6, f6, Bxf6, Rg7, Rxa8#
-    ("2r3k1/p4p2/3Rp2p/1p2P1pK/8/1P4P1/P3Q2P/1q6 b - - 0 1", "b1g6", -30_000 + 5),  // Qg6+, Kg4, Qf5+, Kh5, Qh3#
-    ("1k5r/pP3ppp/3p2b1/1BN1n3/1Q2P3/P1B5/KP3P1P/7q w - - 1 0", "c5a6", 30_000 - 5),  // Na6, Kxb7, Kxa6, Qb5#
-    ("3r4/pR2N3/2pkb3/5p2/8/2B5/qP3PPP/4R1K1 w - - 1 0", "c3e5", 30_000 - 5),  // Be5, Kc5, Rc1, Bc4, b4#
-    ("R6R/1r3pp1/4p1kp/3pP3/1r2qPP1/7P/1P1Q3K/8 w - - 1 0", "f4f5", 30_000 - 5),  // f5+, exf5, Qxh6+, gxh6, Rag8#
-    ("4r1k1/5bpp/2p5/3pr3/8/1B3pPq/PPR2P2/2R2QK1 b - - 0 1", "e5e1", -30_000 + 5),  // Re1, Rxe1, Rxe1, Qxe1, Qg2#
-
-    // mate in 3 plies
-    ("r2qk2r/pb4pp/1n2Pb2/2B2Q2/p1p5/2P5/2B2PPP/RN2R1K1 w - - 1 0", "f5g6", 30_000 - 3),  // Qg6+, hxg6, Bxg6#
-    ("6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1", "g2g1", -30_000 + 3),  // Rg1+, Kxg1, Rxf1#
-    ("8/2k2p2/2b3p1/P1p1Np2/1p3b2/1P1K4/5r2/R3R3 b - - 0 1", "c6b5", -30_000 + 3),  // Bb5+, Nc4, Rd2#
-    ("6k1/5p2/1p5p/p4Np1/5q2/Q6P/PPr5/3R3K w - - 1 0", "a3f8", 30_000 - 3),  // Qf8+, Kxf8, Rd8#
-    ("r1b2k1r/ppppq3/5N1p/4P2Q/4PP2/1B6/PP5P/n2K2R1 w - - 1 0", "h5h6", 30_000 - 3),  // Qxh6+, Rxh6, Rg8#
-
-    // mate in 2 plies (self made)
-    ("r2qk2r/pb4pp/1n2PbQ1/2B5/p1p5/2P5/2B2PPP/RN2R1K1 b - - 2 1", "h7g6", 30_000 - 2),  // hxg6, Bxg6#
-    ("6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3r1P/2BRRNrK w - - 1 2", "h1g1", -30_000 + 2),  // Kxg1, Rxf1#
-    ("8/2k2p2/6p1/Pbp1Np2/1p3b2/1P1K4/5r2/R3R3 w - - 1 2", "e5c4", -30_000 + 2),  // Nc4, Rd2#
-    ("5Qk1/5p2/1p5p/p4Np1/5q2/7P/PPr5/3R3K b - - 2 1", "g8f8", 30_000 - 2),  // Kxf8, Rd8#
-    ("r1b2k1r/ppppq3/5N1Q/4P3/4PP2/1B6/PP5P/n2K2R1 b - - 0 1", "h8h6", 30_000 - 2),  // Rxh6, Rg8#
+static FIFTY_MOVE_FENS: &[(&str, i32)] = &[
+    // any move, but a capture or pawn move, draws
+    ("8/8/4k3/8/8/3K4/8/R7 w - - 99 80", 0),
+    ("8/8/4k3/8/8/3K4/8/r7 b - - 99 80", 0),
+    ("8/8/4k3/8/8/3K4/2p5/R7 w - - 99 80", 505),  // Kxc2 resets the counter
+    ("8/8/4k3/8/8/3K4/8/R7 w - - 149 105", 0),  // the last count before the 75 move rule
+];
+
+static SEE_FENS: &[(&str, &str, i32)] = &[
+    // static exchange evaluation of a move, in centipawns
+    ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5", 100),  // undefended pawn
+    ("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5", -200),  // x-rays on both sides
+    ("4k3/3r4/8/3p4/8/8/3R4/3R1K2 w - - 0 1", "d2d5", 100),  // the rook behind recaptures
+    ("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 0),  // en passant, recaptured
+    ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100),
+    ("7r/P7/8/8/8/8/k7/4K3 w - - 0 1", "a7a8q", -100),  // the new queen is lost
+    ("4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1", "c3e4", -300),  // quiet move onto an attacked square
+    ("4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1", "c3b5", 0),
+    ("3k4/3q4/8/3p4/8/8/3Q4/3K4 w - - 0 1", "d2d5", -800),  // the queen is recaptured
+];
+
+static SAN_FENS: &[(&str, &str, Result<&str, SanError>)] = &[
+    // SAN, and the move it resolves to (in canonical form, so it has to round trip)
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "Nf3", Ok("g1f3")),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O", Ok("e1g1")),
+    ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "O-O-O", Ok("e8c8")),
+    ("8/P7/8/8/8/8/8/k1K5 w - - 0 1", "a8=Q#", Ok("a7a8q")),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "exf6", Ok("e5f6")),
+    ("rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1", "Nbd2", Ok("b1d2")),
+    ("4k3/8/8/8/8/R7/8/R3K3 w - - 0 1", "R1a2", Ok("a1a2")),
+
+    // malformed, illegal or ambiguous SAN
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "Nf9", Err(SanError::Malformed)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e5", Err(SanError::NoSuchMove)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "O-O", Err(SanError::NoSuchMove)),
+    ("rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1", "Nd2", Err(SanError::Ambiguous)),
+];
+
+static PGN_GAMES: &[(&str, &str)] = &[
+    // canonical PGN (so it has to round trip), and the FEN after the main line
+    ("[Event \"Test\"]
+[Site \"?\"]
+[Date \"2024.01.01\"]
+[Round \"1\"]
+[White \"A \\\"the first\\\"\"]
+[Black \"B\"]
+[Result \"1-0\"]
+
+{Ruy Lopez} 1. e4 e5 2. Nf3 {the main line} 2... Nc6 (2... d6 3. d4 $1 (3. Bc4)
+3... exd4) 3. Bb5 a6 $6 4. Ba4 1-0
+", "r1bqkbnr/1ppp1ppp/p1n5/4p3/B3P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 1 4"),
+    ("[Event \"?\"]
+[Site \"?\"]
+[Date \"????.??.??\"]
+[Round \"?\"]
+[White \"?\"]
+[Black \"?\"]
+[Result \"1/2-1/2\"]
+[SetUp \"1\"]
+[FEN \"3q2k1/1QR2ppp/8/8/8/8/6PK/8 b - - 0 1\"]
+
+1... Qh4+ 2. Kg1 Qe1+ 3. Kh2 Qh4+ 1/2-1/2
+", "6k1/1QR2ppp/8/8/7q/8/6PK/8 w - - 5 4"),
+];
+
+static PGN_ERRORS: &[(&str, &str)] = &[
+    ("1. e4 e5 2. Ke3 *", "move \"Ke3\": no legal move matches"),
+    ("1. e4 (1. d4 *", "unbalanced parenthesis"),
+    ("1. e4 e5) *", "unbalanced parenthesis"),
+    ("1. e4 {unfinished *", "unterminated comment"),
+    ("[Event Test]\n1. e4 *", "malformed tag pair"),
+    ("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n*", "invalid FEN tag: white king is missing"),
+];
+
+static INVALID_FENS: &[(&str, FenError)] = &[
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", FenError::MissingField("50 move counter")),
+    ("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankCount(7)),
+    ("rnbqkbnr/pppppppp/8/8/8/7/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(3)),
+    ("rnbqkbnr/pppppppp/8/8/8/45/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::BadRankLength(3)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1", FenError::BadRankLength(1)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1", FenError::UnknownPieceChar('X')),
+    ("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1", FenError::MissingKing(false)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKKBNR w kq - 0 1", FenError::TwoKings(true)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNp w Qkq - 0 1", FenError::PawnOnBackRank(Square::H1)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::BadSideToMove),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1", FenError::BadCastlingField),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKkq - 0 1", FenError::BadCastlingField),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1", FenError::BadCastlingField),  // no rook on h1
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1", FenError::BadEnPassantSquare),
+    ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1", FenError::EnPassantInconsistent(Square::E3)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq e3 0 1", FenError::EnPassantInconsistent(Square::E3)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", FenError::BadCounter),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 -1", FenError::BadCounter),
+    ("8/8/4k3/8/8/3K4/8/R7 w - - 150 105", FenError::BadCounter),  // beyond the 75 move rule
+    ("4r1k1/8/8/8/8/8/8/4K3 b - - 0 1", FenError::SideNotToMoveInCheck),
 ];
 
 
@@ -99,6 +251,7 @@ fn test_make_unmake<IsMaxDepth: Bool>(board: &mut Board, depth: u8) {
             if board.en_passant_square != copy.en_passant_square {println!("ep sq");}
             if board.zobrist_hash != copy.zobrist_hash {println!("Hash");}
             if board.fifty_move_counter != copy.fifty_move_counter {println!("50");}
+            if board.full_move_counter != copy.full_move_counter {println!("full");}
             if board.history != copy.history {println!("History");}
 
             println!();
@@ -112,140 +265,111 @@ fn test_make_unmake<IsMaxDepth: Bool>(board: &mut Board, depth: u8) {
     }
 }
 
-#[derive(Default)]
-pub struct PerftInfo {
-    duration: Duration,
-    nodes_visited: usize,
-    captures: usize,
-    en_passant: usize,
-    castles: usize,
-    promotions: usize,
-    terminals: usize/*,
-    checks: usize,
-    discovery_checks: usize,
-    double_checks: usize,
-    checkmates: usize*/
-}
+// (file, rank) steps of the pieces, for the naive reference the lookup tables are tested against
+static X_DIRECTIONS: &[(i8, i8)] = &[(1, 1), (-1, 1), (-1, -1), (1, -1)];
+static PLUS_DIRECTIONS: &[(i8, i8)] = &[(1, 0), (0, 1), (-1, 0), (0, -1)];
+static KING_STEPS: &[(i8, i8)] = &[(1, 1), (-1, 1), (-1, -1), (1, -1), (1, 0), (0, 1), (-1, 0), (0, -1)];
+static KNIGHT_STEPS: &[(i8, i8)] = &[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
 
-impl PerftInfo {
-    pub fn visualize(self: &Self) {
-        println!(
-            "\
-            \tNodes visited : {}.\n\
-            \tThereof:\n\
-            \t- Captures    : {},\n\
-            \t- En-passants : {},\n\
-            \t- Castles     : {},\n\
-            \t- Promotions  : {},\n\
-            \t- Terminals   : {},\n\
-            \tDuration: {}ms, i. e. {} MN/s.\
-            ",
-            self.nodes_visited, self.captures, self.en_passant, self.castles,
-            self.promotions, self.terminals, self.duration.as_millis(),
-            (self.nodes_visited as f64)/(1_000_000f64 * self.duration.as_secs() as f64)
-        )
-    }
-}
 
-fn perft(board: &mut Board, depth: u8) {
+fn ray_walk(square: usize, occupation: Bitboard, directions: &[(i8, i8)], slides: bool) -> Vec<Vec<Bitboard>> {
     /*
-    Simulate all possible sequences of (half)moves up until given depth and visualize results in
-    console.
+    for each direction the squares reached by stepping from the square, in order: until leaving the
+    board or hitting an occupied square (inclusive) if sliding, exactly one step otherwise
     */
 
-    // toggle debugging prints
-    const PRINT_CAPTURES: bool = false;
-    const PRINT_TERMINALS: bool = false;
-
-    fn inner_perft(board: &mut Board, depth: u8, perft_info: &mut PerftInfo) {
-
-        // get legal moves
-        let legal_moves = board.get_legal_moves();
-
-        // loop through legal moves, make them and recurse
-        let mut n_moves = 0;
-        for r#move in legal_moves {
-            n_moves += 1;
-
-            if PRINT_CAPTURES && r#move.is_capture() {
-                println!("Current board:");
-                board.visualize();
-                print!("Capture:");
-                r#move.visualize();
-                println!();
+    let mut rays = Vec::new();
+    for &(d_file, d_rank) in directions {
+        let mut ray = Vec::new();
+        let (mut file, mut rank) = ((square % 8) as i8, (square / 8) as i8);
+        loop {
+            file += d_file;
+            rank += d_rank;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
             }
-
-            // update perft info
-            if depth == 1 {
-                perft_info.nodes_visited += 1;
-                if r#move.is_capture() { perft_info.captures += 1; }
-                if r#move.is_en_passant() { perft_info.en_passant += 1; }
-                if r#move.is_castling() { perft_info.castles += 1; }
-                if r#move.is_promotion() { perft_info.promotions += 1; }
-            }
-
-            if depth > 1 {
-                // make move, recurse, unmake move
-                board.make_move(r#move);
-                inner_perft(board, depth - 1, perft_info);
-                board.unmake_move();
-            }
-        }
-
-        // no legal moves: terminal state
-        if depth == 1 && n_moves == 0 {
-            perft_info.terminals += 1;
-            if PRINT_TERMINALS {
-                println!("Terminal node:");
-                board.visualize();
+            let reached = Bitboard(1 << (8*rank + file));
+            ray.push(reached);
+            if !slides || (occupation & reached).has_bits() {
+                break;
             }
         }
+        rays.push(ray);
     }
+    rays
+}
 
-    // do perft
-    for r#move in board.get_legal_moves() {
-
-        // go to child
-        board.make_move(r#move);
-
-        // do perft of child and time
-        let now = Instant::now();
-        let mut perft_info_of_move = PerftInfo::default();
-        inner_perft(board, depth-1, &mut perft_info_of_move);
-        perft_info_of_move.duration = now.elapsed();
-
-        // back to parent
-        board.unmake_move();
 
-        // visualize result for child
-        print!("\n");
-        r#move.visualize();
-        print!(":\n");
-        perft_info_of_move.visualize();
-    }
+fn union(squares: &[Bitboard]) -> Bitboard {
+    squares.iter().fold(Bitboard(0), |union, &square| union | square)
 }
 
 #[cfg(test)]
 mod tests {
 
+    use std::time::Instant;
+
+    use bitboards::Bitboard;
     use generic_magic::True;
-    use search::{minimax::minimax, alpha_beta::alpha_beta, transposition_table::TranspositionTable};
+    use lookups::{X_PEXT_MASK, PLUS_PEXT_MASK, KNIGHT_MASK, BISHOP_MASK, ROOK_MASK, KING_MASK, PATH_WITHOUT_END};
+    use lookups::sliders::{
+        SLIDER_BACKEND,
+        magic_bishop_attacks,
+        magic_rook_attacks,
+        software_bishop_attacks,
+        software_rook_attacks
+    };
+    use search::{minimax::minimax, alpha_beta::{alpha_beta, alpha_beta_multi_pv, alpha_beta_with_aspiration, mate_search}, parameters::SearchParameters, search_info::{Bound, history_bonus}, traits::{AlphaBetaSearchFunctionality, SearchableMove}, transposition_table::{TranspositionTable, TranspositionTableFileError}, MATE_EVALUATION};
 
     use crate::{
         board::Board,
+        epd::EpdEntry,
         moves::Move,
+        perft::PerftEntry,
+        pgn::PgnGame,
         testing::{
             PERFT_FENS,
-            MATE_FENS,
+            EN_PASSANT_EVASION_FENS,
+            ASPIRATION_FENS,
+            PERFT_EPD,
+            PERFT_INFOS,
+            CHESS960_CASTLES,
+            MATE_EPD,
+            PERPETUAL_FENS,
+            DRAW_FENS,
+            FIFTY_MOVE_FENS,
+            SEE_FENS,
+            INVALID_FENS,
+            SAN_FENS,
+            PGN_GAMES,
+            PGN_ERRORS,
+            X_DIRECTIONS,
+            PLUS_DIRECTIONS,
+            KING_STEPS,
+            KNIGHT_STEPS,
             test_make_unmake,
-            perft
+            ray_walk,
+            union
         }
     };
 
     const MAKE_UNMAKE_DEPTH: u8 = 3;
     const MATE_SEARCH_DEPTH: u8 = 6;
+    const SHARED_SEARCH_THREADS: usize = 3;
+    const SMALL_TRANSPOSITION_TABLE_CAPACITY: usize = 64;
+    const AGING_TRANSPOSITION_TABLE_CAPACITY: usize = 1000;  // all sampled for the fill level
+    const CAPACITY_OFFSET: usize = 8 + 4 * 4;  // of the capacity in the header of a saved table
+    const PERPETUAL_SEARCH_DEPTH: u8 = 6;
+    const PV_SEARCH_DEPTH: u8 = 5;
+    const MULTI_PV_DEPTH: u8 = 4;
+    const MULTI_PV_LINES: usize = 4;
+    const SEARCH_MOVES_DEPTH: u8 = 4;
+    const FIFTY_MOVE_SEARCH_DEPTH: u8 = 4;
     const COMPARE_DEPTH: u8 = 3;
+    const ASPIRATION_DEPTH: u8 = 4;
+    const ASPIRATION_TOLERANCE: u32 = 25;  // centipawns, what the late move heuristics may cost
     const PERFT_DEPTH: u8 = 3;
+    const PERFT_SUITE_MAX_NODES: usize = 100_000;
 
 
     #[test]
@@ -253,11 +377,14 @@ mod tests {
         // test whether make_move and unmake_move are inverse to each (test on multiple boards)
 
         println!("Starting perft!");
-        for &fen in PERFT_FENS {
-            println!("FEN: {fen}\n");
+        let chess960_boards = PerftEntry::read_all(PERFT_EPD).unwrap().into_iter()
+            .map(|entry| entry.board)
+            .filter(|board| board.chess960);
+        for mut board in PERFT_FENS.iter().map(|fen| Board::from_fen(fen)).chain(chess960_boards) {
+            println!("FEN: {}\n", board.to_fen());
 
             test_make_unmake::<True/*IsMaxDepth*/>(
-                &mut Board::from_fen(fen),
+                &mut board,
                 MAKE_UNMAKE_DEPTH
             );
         }
@@ -270,16 +397,327 @@ mod tests {
         // TODO: Work through PVs and check TT in that way.
 
         println!("Starting!");
-        for (fen, algebraic_move, evaluation) in MATE_FENS {
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("\nFEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+
+            let mut board = entry.board.clone();
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = alpha_beta(
+                &mut board,
+                MATE_SEARCH_DEPTH,
+                &[],
+                &SearchParameters::default(),
+                &transposition_table
+            );
+
+            // the exact score is given from the side to move's perspective
+            let evaluation = if board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            print!("Expected:");
+            entry.best_moves[0].visualize();
+            print!(" with score: {evaluation}");
+            print!(", got:");
+            info.best_move.unwrap().visualize();
+            print!(" with score {}\n", info.evaluation);
+            if !entry.is_solved_by(info.best_move.unwrap(), info.evaluation) || info.evaluation != evaluation {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_transposition_table_multiple() {
+        // threads searching the same position concurrently, sharing one transposition table, all find the mate
+
+        let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            std::thread::scope(|scope| {
+                let searches: Vec<_> = (0..SHARED_SEARCH_THREADS).map(|_| scope.spawn(|| {
+                    let mut board = entry.board.clone();
+                    let info = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+                    (info.best_move.unwrap(), info.evaluation)
+                })).collect();
+
+                for search in searches {
+                    let (best_move, best_evaluation) = search.join().unwrap();
+                    assert!(entry.is_solved_by(best_move, best_evaluation) && best_evaluation == evaluation);
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_small_transposition_table_multiple() {
+        // a table much smaller than the search tree, where entries are replaced all the time, still finds the mates
+
+        let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+        transposition_table.set_capacity_to(SMALL_TRANSPOSITION_TABLE_CAPACITY);
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            let mut board = entry.board.clone();
+            let info = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            assert!(entry.is_solved_by(info.best_move.unwrap(), info.evaluation) && info.evaluation == evaluation);
+
+            // the principal variation consists of legal moves
+            for &r#move in &info.principal_variation {
+                assert!(board.get_legal_moves().contains(&r#move));
+                board.make_move(r#move);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transposition_table_aging_multiple() {
+        // entries of an earlier search don't count as filled, yet searching again with them still finds the mates
+
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            transposition_table.set_capacity_to(AGING_TRANSPOSITION_TABLE_CAPACITY);
+            for _ in 0..2 {
+                transposition_table.new_search();
+                assert_eq!(transposition_table.fill_level_per_mill(), 0);
+
+                let mut board = entry.board.clone();
+                let info = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+                assert!(entry.is_solved_by(info.best_move.unwrap(), info.evaluation) && info.evaluation == evaluation);
+                assert!(transposition_table.fill_level_per_mill() > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clear_transposition_table_multiple() {
+        // a cleared table is empty and searching with it still finds the mates
+
+        let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+        transposition_table.set_size_in_mb(1);
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            transposition_table.clear();
+            assert_eq!(transposition_table.fill_level_per_mill(), 0);
+
+            let mut board = entry.board.clone();
+            let info = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            assert!(entry.is_solved_by(info.best_move.unwrap(), info.evaluation) && info.evaluation == evaluation);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_transposition_table_multiple() {
+        // a loaded table holds what was saved, so searching the position again needs (almost) no nodes
+
+        let path = std::env::temp_dir().join(format!("test_transposition_table_{}.tt", std::process::id()));
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+
+            let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            transposition_table.set_capacity_to(AGING_TRANSPOSITION_TABLE_CAPACITY);
+            let mut board = entry.board.clone();
+            let searched = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            transposition_table.save(&path).expect("Saving should succeed");
+
+            let fill_level = transposition_table.fill_level_per_mill();
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::load(&path).expect("Loading should succeed");
+            assert_eq!(transposition_table.fill_level_per_mill(), fill_level);
+            transposition_table.new_search();
+            let reloaded = alpha_beta(&mut board, MATE_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            assert!(entry.is_solved_by(reloaded.best_move.unwrap(), reloaded.evaluation) && reloaded.evaluation == evaluation);
+            assert!(reloaded.nodes_visited < searched.nodes_visited);
+        }
+
+        // anything else is rejected
+        std::fs::write(&path, b"not a transposition table").unwrap();
+        assert!(matches!(TranspositionTable::<Board>::load(&path), Err(TranspositionTableFileError::NotATable)));
+
+        // as are tables whose header claims more (or fewer) entries than the file holds
+        let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+        transposition_table.set_capacity_to(SMALL_TRANSPOSITION_TABLE_CAPACITY);
+        transposition_table.save(&path).expect("Saving should succeed");
+        let loaded = TranspositionTable::<Board>::load(&path).expect("Loading should succeed");
+        assert_eq!(loaded.size_in_mb(), 1);  // (the smallest size a GUI may set)
+        let saved = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &saved[..saved.len() - 1]).unwrap();
+        assert!(matches!(TranspositionTable::<Board>::load(&path), Err(TranspositionTableFileError::NotATable)));
+        let mut oversized = saved.clone();
+        oversized[CAPACITY_OFFSET..CAPACITY_OFFSET + 8].copy_from_slice(&(u64::MAX - 3).to_le_bytes());
+        std::fs::write(&path, &oversized).unwrap();
+        assert!(matches!(TranspositionTable::<Board>::load(&path), Err(TranspositionTableFileError::NotATable)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_principal_variation_multiple() {
+        // the principal variation is legal and leads to the position whose evaluation was reported
+
+        let mate_fens = EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid").iter()
+            .map(|entry| entry.board.to_fen()).collect::<Vec<String>>();
+        let fens = PERFT_FENS.iter().map(|fen| fen.to_string())
+            .chain(PERPETUAL_FENS.iter().map(|(fen, _, _)| fen.to_string()))
+            .chain(mate_fens);
+        for fen in fens {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(&fen);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = alpha_beta(&mut board, PV_SEARCH_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            assert_eq!(info.principal_variation.first().copied(), info.best_move);
+
+            for &r#move in &info.principal_variation {
+                assert!(board.get_legal_moves().contains(&r#move));
+                board.make_move(r#move);
+            }
+
+            // the last position is mate, a draw, or (quiet or by standing pat) evaluated statically
+            let distance_to_root = info.principal_variation.len() as i32;
+            let evaluation = if board.get_legal_moves().is_empty() {
+                match (AlphaBetaSearchFunctionality::is_check(&board), board.whites_turn) {
+                    (true, true) => -MATE_EVALUATION + distance_to_root,
+                    (true, false) => MATE_EVALUATION - distance_to_root,
+                    (false, _) => 0
+                }
+            } else if AlphaBetaSearchFunctionality::is_draw(&board) {
+                0
+            } else {
+                AlphaBetaSearchFunctionality::evaluate(&board)
+            };
+            assert_eq!(evaluation, info.evaluation);
+        }
+    }
+
+    #[test]
+    fn test_mate_search_multiple() {
+        // a mate search proves the mate in the given number of moves, but not in fewer
+
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            // (in the other positions the side to move gets mated)
+            let Some(n_moves) = entry.direct_mate() else {
+                continue;
+            };
+            let n_moves = n_moves as u8;
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+            let mut board = entry.board.clone();
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = mate_search(&mut board, 2 * n_moves, n_moves, &[], &SearchParameters::default(), &transposition_table);
+            assert_eq!(info.bound, Bound::Exact);
+            assert!(entry.is_solved_by(info.best_move.unwrap(), info.evaluation) && info.evaluation == evaluation);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = mate_search(&mut board, 2 * n_moves, n_moves - 1, &[], &SearchParameters::default(), &transposition_table);
+            assert_ne!(info.bound, Bound::Exact);
+        }
+    }
+
+    #[test]
+    fn test_multi_pv_multiple() {
+        // the lines start with distinct legal moves, best first, and the best is the search's result
+
+        for fen in PERFT_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+            let legal_moves = board.get_legal_moves();
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let multi = alpha_beta_multi_pv(&mut board, MULTI_PV_DEPTH, MULTI_PV_LINES, &[], &SearchParameters::default(), &transposition_table);
+
+            assert_eq!(multi.root_moves.len(), MULTI_PV_LINES.min(legal_moves.len()));
+            let best_line = &multi.root_moves[0];
+            assert_eq!((multi.best_move, multi.evaluation), (Some(best_line.r#move), best_line.evaluation));
+            assert_eq!(multi.principal_variation, best_line.principal_variation);
+            for (index, line) in multi.root_moves.iter().enumerate() {
+                assert!(legal_moves.contains(&line.r#move));
+                assert_eq!(line.principal_variation.first(), Some(&line.r#move));
+                assert!(multi.root_moves[..index].iter().all(|better| better.r#move != line.r#move));
+                if index > 0 {
+                    let previous = &multi.root_moves[index - 1];
+                    assert!(if board.whites_turn {previous.evaluation >= line.evaluation} else {previous.evaluation <= line.evaluation});
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_moves_multiple() {
+        // restricted to every other legal move, only those are searched at the root, in every mode
+
+        for fen in PERFT_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+            let search_moves: Vec<Move> = board.get_legal_moves().into_iter().step_by(2).collect();
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = alpha_beta(&mut board, SEARCH_MOVES_DEPTH, &search_moves, &SearchParameters::default(), &transposition_table);
+            assert!(search_moves.contains(&info.best_move.unwrap()));
+            assert_eq!(info.principal_variation.first(), info.best_move.as_ref());
+
+            // the shared transposition table holds the positions below the root, but not the restricted root
+            let aspiration = alpha_beta_with_aspiration(
+                &mut board, SEARCH_MOVES_DEPTH, info.evaluation, &search_moves, &SearchParameters::default(), &transposition_table, |_| {}
+            );
+            assert!(search_moves.contains(&aspiration.best_move.unwrap()));
+
+            let multi = alpha_beta_multi_pv(&mut board, SEARCH_MOVES_DEPTH, search_moves.len() + 1, &search_moves, &SearchParameters::default(), &transposition_table);
+            assert_eq!(multi.root_moves.len(), search_moves.len());
+            assert!(multi.root_moves.iter().all(|line| search_moves.contains(&line.r#move)));
+        }
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        // test whether repetitions are counted when shuffling knights back and forth
+
+        let mut board = Board::default();
+        assert_eq!(board.repetition_count(), 0);
+
+        for count in 1..=2 {
+            for algebraic_move in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                assert!(!board.is_threefold_repetition());
+                let r#move = Move::from_algebraic(algebraic_move, &board);
+                board.make_move(r#move);
+            }
+            assert_eq!(board.repetition_count(), count);
+            assert!(board.is_repetition());
+        }
+        assert!(board.is_threefold_repetition());
+
+        // a pawn move is irreversible, hence counting starts anew
+        for algebraic_move in ["e2e4", "g8f6", "g1f3", "f6g8", "f3g1", "g8f6"] {
+            let r#move = Move::from_algebraic(algebraic_move, &board);
+            board.make_move(r#move);
+        }
+        assert_eq!(board.repetition_count(), 1);
+        assert!(!board.is_threefold_repetition());
+    }
+
+    #[test]
+    fn test_perpetual_multiple() {
+        // test whether search finds (and evaluates as draw) a perpetual check in multiple positions
+
+        println!("Starting!");
+        for (fen, algebraic_move, evaluation) in PERPETUAL_FENS {
             println!("\nFEN: {fen}, move: {algebraic_move}");
 
             let mut board = Board::from_fen(fen);
             let r#move = Move::from_algebraic(algebraic_move, &board);
-            let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
             let info = alpha_beta(
                 &mut board,
-                MATE_SEARCH_DEPTH,
-                &mut transposition_table
+                PERPETUAL_SEARCH_DEPTH,
+                &[],
+                &SearchParameters::default(),
+                &transposition_table
             );
 
             print!("Expected:");
@@ -294,6 +732,539 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fen_round_trip_multiple() {
+        // test whether to_fen reproduces the FEN a board was built from (on multiple boards)
+
+        let fens = PERFT_FENS.iter().chain(DRAW_FENS.iter().map(|(fen, _)| fen));
+        for &fen in fens {
+            println!("FEN: {fen}");
+            assert_eq!(Board::from_fen(fen).to_fen(), fen);
+        }
+
+        // the mate suite's positions, with the counters of their "hmvc" and "fmvn" operations filled in
+        let entries = EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid");
+        for (line, entry) in MATE_EPD.lines().zip(entries) {
+            let fields: Vec<&str> = line.split_whitespace().take(4).collect();
+            let fen = format!("{} {} {}", fields.join(" "), entry.board.fifty_move_counter, entry.board.full_move_counter);
+            println!("FEN: {fen}");
+            assert_eq!(Board::from_fen(&fen).to_fen(), fen);
+            assert_eq!(entry.board.to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_fen_after_moves() {
+        // test whether to_fen keeps track of en-passant squares and both counters
+
+        let mut board = Board::default();
+        let expected_fens = [
+            ("e2e4", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+            ("c7c5", "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2"),
+            ("g1f3", "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"),
+            ("d8a5", "rnb1kbnr/pp1ppppp/8/q1p5/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"),
+        ];
+
+        for (algebraic_move, fen) in expected_fens {
+            let r#move = Move::from_algebraic(algebraic_move, &board);
+            board.make_move(r#move);
+            assert_eq!(board.to_fen(), fen);
+        }
+
+        // unmaking restores the counters as well
+        for _ in 0..expected_fens.len() {
+            board.unmake_move();
+        }
+        assert_eq!(board.to_fen(), Board::default().to_fen());
+    }
+
+    #[test]
+    fn test_invalid_fen_multiple() {
+        // test whether malformed or illegal FENs are rejected with the right error (on multiple FENs)
+
+        for (fen, error) in INVALID_FENS {
+            println!("FEN: {fen}, expected: {:?}", error);
+            assert!(Board::try_from_fen(fen) == Err(*error));
+        }
+    }
+
+    #[test]
+    fn test_san_multiple() {
+        // test SAN parsing and writing along the predicted mating lines (in multiple positions)
+
+        for entry in EpdEntry::read_all(MATE_EPD).unwrap() {
+            let line = entry.operands("pv").unwrap();
+            println!("\nFEN: {}, line: {}", entry.board.to_fen(), line.join(" "));
+
+            let mut board = entry.board.clone();
+            for (ply, san) in line.iter().enumerate() {
+                let r#move = Move::from_san(san, &board).expect("SAN should resolve to a legal move");
+                if ply == 0 {
+                    assert!(entry.best_moves == vec![r#move]);
+                }
+                assert_eq!(r#move.to_san(&board), *san);
+                board.make_move(r#move);
+            }
+
+            // every line ends in mate
+            assert!(board.is_check() && board.get_legal_moves().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_san_edge_cases_multiple() {
+        // test castling, promotions, en-passant, disambiguation and errors in SAN (in multiple positions)
+
+        for (fen, san, expected) in SAN_FENS {
+            println!("FEN: {fen}, SAN: {san}, expected: {:?}", expected);
+
+            let board = Board::from_fen(fen);
+            let result = Move::from_san(san, &board);
+            assert_eq!(result.map(|r#move| r#move.to_string()), expected.map(str::to_owned));
+            if let Ok(r#move) = result {
+                assert_eq!(r#move.to_san(&board), *san);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pgn_multiple() {
+        // test reading, replaying and writing PGN (on multiple games)
+
+        for (pgn, fen) in PGN_GAMES {
+            println!("PGN:\n{pgn}");
+
+            let game = PgnGame::read(pgn).expect("PGN should be valid");
+            assert_eq!(game.final_board().unwrap().to_fen(), *fen);
+            assert_eq!(game.to_pgn().unwrap(), *pgn);
+        }
+
+        // all games as one collection
+        let collection = PGN_GAMES.iter().map(|(pgn, _)| *pgn).collect::<Vec<&str>>().join("\n");
+        assert_eq!(PgnGame::read_all(&collection).unwrap().len(), PGN_GAMES.len());
+    }
+
+    #[test]
+    fn test_pgn_loose_formatting() {
+        // test glued move numbers, suffix annotations, rest-of-line comments and a missing roster
+
+        let error = PgnGame::read("1.e4 e5!? 2.Nf3 ; a comment\n2...Nc6 3.O-O?? *").unwrap_err();
+        assert_eq!(error.to_string(), "move \"O-O\": no legal move matches");
+
+        let game = PgnGame::read("1.e4 e5!? 2.Nf3 ; a comment\n2...Nc6 3.Bb5 a6 *").unwrap();
+        assert_eq!(game.moves[1].nags, vec![5]);
+        assert_eq!(game.moves[2].comment_after.as_deref(), Some("a comment"));
+        assert_eq!(game.to_pgn().unwrap(), "\n1. e4 e5 $5 2. Nf3 {a comment} 2... Nc6 3. Bb5 a6 *\n");
+    }
+
+    #[test]
+    fn test_pgn_errors_multiple() {
+        // test whether broken PGN is rejected with the right error (on multiple PGNs)
+
+        for (pgn, error) in PGN_ERRORS {
+            println!("PGN: {pgn}, expected: {error}");
+            assert_eq!(PgnGame::read(pgn).unwrap_err().to_string(), *error);
+        }
+    }
+
+    #[test]
+    fn test_epd_parsing() {
+        // test quoted operands, move lists, counters and errors in EPD
+
+        let entry = EpdEntry::parse(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm Nf3 e4; am g4; hmvc 3; fmvn 7; id \"a; b\";"
+        ).unwrap();
+        assert_eq!(entry.id(), Some("a; b"));
+        assert_eq!(entry.board.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 7");
+        assert!(entry.best_moves.iter().map(|r#move| r#move.to_string()).eq(["g1f3", "e2e4"]));
+        assert!(entry.avoid_moves.iter().map(|r#move| r#move.to_string()).eq(["g2g4"]));
+        assert!(entry.is_solved_by(entry.best_moves[1], 0));
+        assert!(!entry.is_solved_by(entry.avoid_moves[0], 0));
+
+        for (line, error) in [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq", "invalid position: missing en-passant field"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm Nf3", "malformed operation \"bm\""),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm Nf4;", "move \"Nf4\": no legal move matches"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - hmvc x;", "malformed operation \"hmvc\""),
+        ] {
+            println!("EPD: {line}, expected: {error}");
+            assert_eq!(EpdEntry::parse(line).err().unwrap().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn test_perft_suite() {
+        // compare node counts against the perft suite (as far as it is fast enough for a test)
+
+        for entry in PerftEntry::read_all(PERFT_EPD).unwrap() {
+            let mut board = entry.board.clone();
+            println!("FEN: {}", board.to_fen());
+            for &(depth, nodes) in entry.node_counts.iter().filter(|(_, nodes)| *nodes <= PERFT_SUITE_MAX_NODES) {
+                assert_eq!(board.perft(depth), nodes, "{} at depth {depth}", board.to_fen());
+            }
+
+            // divide adds up to the same count
+            let divide = board.perft_divide(2);
+            assert_eq!(divide.len(), board.get_legal_moves().len());
+            assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<usize>(), board.perft(2));
+
+            // Chess960 positions survive being written as X-FEN
+            if board.chess960 {
+                assert!(Board::from_fen(&board.to_fen()) == board, "{}", board.to_fen());
+            }
+        }
+    }
+
+    #[test]
+    fn test_en_passant_evasion_multiple() {
+        // the en-passant capture of a checking pawn is among the evasions (next to seven king moves)
+
+        for (fen, algebraic_move) in EN_PASSANT_EVASION_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+            assert!(board.is_check());
+            let legal_moves = board.get_legal_moves();
+            assert!(legal_moves.iter().any(|r#move| r#move.is_en_passant() && r#move.to_string() == *algebraic_move));
+            assert_eq!(board.perft(1), 8);
+        }
+    }
+
+    #[test]
+    fn test_perft_info_multiple() {
+        // compare the node breakdowns against known results
+
+        for (fen, depth, expected) in PERFT_INFOS {
+            println!("FEN: {fen}");
+            assert_eq!(Board::from_fen(fen).perft_info(*depth), *expected);
+        }
+    }
+
+    #[test]
+    fn test_slider_backends() {
+        // every backend gives the attacks of BISHOP_MASK/ROOK_MASK for every occupancy of every square
+
+        println!("Build uses {:?}", SLIDER_BACKEND);
+
+        for square in 0..64 {
+            for (pext_mask, masks, magic_attacks, software_attacks) in [
+                (X_PEXT_MASK[square], &BISHOP_MASK[square][..], magic_bishop_attacks as fn(usize, Bitboard) -> Bitboard, software_bishop_attacks as fn(usize, Bitboard) -> Bitboard),
+                (PLUS_PEXT_MASK[square], &ROOK_MASK[square][..], magic_rook_attacks, software_rook_attacks)
+            ] {
+                for (pext_occupancy, &expected) in masks.iter().enumerate().take(1 << pext_mask.count_ones()) {
+                    // squares outside of the mask must not matter
+                    let outside = Bitboard(0x9E37_79B9_7F4A_7C15u64.rotate_left(pext_occupancy as u32) & !pext_mask.0);
+                    let occupation = Bitboard::const_pdep(pext_occupancy as u64, pext_mask) | outside;
+
+                    assert_eq!(occupation.software_pext(pext_mask), pext_occupancy);
+                    assert_eq!(occupation.const_pext(pext_mask), pext_occupancy);
+                    #[cfg(target_arch = "x86_64")]
+                    if is_x86_feature_detected!("bmi2") {
+                        assert_eq!(unsafe { occupation.bmi2_pext(pext_mask) }, pext_occupancy);
+                    }
+                    assert_eq!(magic_attacks(square, occupation), expected, "square {square}, occupation {:x}", occupation.0);
+                    assert_eq!(software_attacks(square, occupation), expected, "square {square}, occupation {:x}", occupation.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookups_against_ray_walk() {
+        // every lookup table against the naive ray walk, for every square (and every relevant occupancy)
+
+        for square in 0..64 {
+            let empty = Bitboard(0);
+            assert_eq!(KNIGHT_MASK[square], union(&ray_walk(square, empty, KNIGHT_STEPS, false).concat()), "knight on {square}");
+            assert_eq!(KING_MASK[square], union(&ray_walk(square, empty, KING_STEPS, false).concat()), "king on {square}");
+
+            for (pext_mask, masks, directions) in [
+                (X_PEXT_MASK[square], &BISHOP_MASK[square][..], X_DIRECTIONS),
+                (PLUS_PEXT_MASK[square], &ROOK_MASK[square][..], PLUS_DIRECTIONS)
+            ] {
+                // the PEXT mask is every ray without its last square, as that one never blocks anything
+                let rays = ray_walk(square, empty, directions, true);
+                let expected_pext_mask = union(&rays.iter().flat_map(|ray| ray.iter().rev().skip(1).copied()).collect::<Vec<_>>());
+                assert_eq!(pext_mask, expected_pext_mask, "PEXT mask of {square}");
+
+                for (pext_occupancy, &attacks) in masks.iter().enumerate().take(1 << pext_mask.count_ones()) {
+                    let occupation = Bitboard::const_pdep(pext_occupancy as u64, pext_mask);
+                    let expected = union(&ray_walk(square, occupation, directions, true).concat());
+                    assert_eq!(attacks, expected, "square {square}, occupation {:x}", occupation.0);
+                }
+            }
+
+            // the path from a square up to (excluding) any square on a line with it
+            let mut expected_paths = [Bitboard(0); 64];
+            for ray in ray_walk(square, empty, KING_STEPS, true) {
+                let mut path = Bitboard(1 << square);
+                for reached in ray {
+                    expected_paths[reached.tzcnt()] = path;
+                    path |= reached;
+                }
+            }
+            assert_eq!(PATH_WITHOUT_END[square], expected_paths, "paths from {square}");
+        }
+    }
+
+    #[test]
+    fn test_chess960_castling_multiple() {
+        // castling written as king takes rook, parsed back and checked for legality
+
+        for &(fen, uci, san) in CHESS960_CASTLES {
+            let mut board = Board::from_fen(fen);
+            board.chess960 = true;
+
+            let r#move = Move::from_algebraic(uci, &board);
+            assert!(r#move.is_castling(), "{fen}: {uci}");
+            let legal_moves = board.get_legal_moves();
+            match san {
+                None => assert!(!legal_moves.contains(&r#move), "{fen}: {uci} should be illegal"),
+                Some(san) => {
+                    assert!(legal_moves.contains(&r#move), "{fen}: {uci} should be legal");
+                    assert_eq!(r#move.to_string(), uci);
+                    assert_eq!(r#move.to_san(&board), san);
+                    assert_eq!(Move::from_san(san, &board), Ok(r#move));
+
+                    // the rook and king end up on the standard squares
+                    let (king_to, rook_from, rook_to) = board.castling_squares(r#move);
+                    let king = r#move.moving_piece();
+                    let rook = board.piece_at(rook_from);
+                    board.make_move(r#move);
+                    assert!(board.piece_at(king_to) == king && board.piece_at(rook_to) == rook, "{fen}: {uci}");
+                    assert!(board.castle_permissions != Board::from_fen(fen).castle_permissions, "{fen}: {uci}");
+                }
+            }
+        }
+
+        // without Chess960 the same castling is written as the king moving two files
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let r#move = Move::from_algebraic("e1g1", &board);
+        assert!(r#move.is_castling() && r#move.to_string() == "e1g1");
+        assert!(Move::from_algebraic("e1h1", &board) == r#move);
+    }
+
+    #[test]
+    fn test_draw_reason_multiple() {
+        // test whether draws by the rules are detected (in multiple positions)
+
+        for (fen, draw_reason) in DRAW_FENS {
+            println!("FEN: {fen}, expected: {:?}", draw_reason);
+            assert_eq!(Board::from_fen(fen).draw_reason(), *draw_reason);
+        }
+    }
+
+    #[test]
+    fn test_fifty_move_multiple() {
+        // test whether search treats the 50 move rule as draw (in multiple positions)
+
+        for (fen, evaluation) in FIFTY_MOVE_FENS {
+            println!("\nFEN: {fen}");
+
+            let mut board = Board::from_fen(fen);
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = alpha_beta(
+                &mut board,
+                FIFTY_MOVE_SEARCH_DEPTH,
+                &[],
+                &SearchParameters::default(),
+                &transposition_table
+            );
+
+            println!("Expected score: {evaluation}, got: {}", info.evaluation);
+            assert_eq!(info.evaluation, *evaluation);
+        }
+    }
+
+    #[test]
+    fn test_null_move_multiple() {
+        // a null move gives the position with the other side to move (and hash), and unmaking it restores the board
+
+        let en_passant_fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        for fen in PERFT_FENS.iter().chain([&en_passant_fen]) {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+            if board.is_check() {
+                continue;
+            }
+            let copy = board.clone();
+
+            board.make_null_move();
+            let passed = Board::from_fen(&board.to_fen());
+            assert!(board.whites_turn != copy.whites_turn);
+            assert!(board.en_passant_square.is_none());
+            assert!(board.zobrist_hash == passed.zobrist_hash);
+            assert_eq!(board.get_legal_moves().len(), passed.get_legal_moves().len());
+            assert!(board.last_move().is_none() && board.last_move_was_null());
+
+            board.unmake_null_move();
+            assert!(board == copy);
+        }
+    }
+
+    #[test]
+    fn test_see_multiple() {
+        // test static exchange evaluation (in multiple positions)
+
+        for (fen, algebraic_move, evaluation) in SEE_FENS {
+            println!("FEN: {fen}, move: {algebraic_move}");
+            let board = Board::from_fen(fen);
+            let r#move = Move::from_algebraic(algebraic_move, &board);
+            assert_eq!(board.see(r#move), *evaluation);
+            assert!(board.see_ge(r#move, *evaluation) && !board.see_ge(r#move, evaluation + 1));
+        }
+    }
+
+    #[test]
+    fn test_see_ge_multiple() {
+        // the threshold variant agrees with the full static exchange evaluation (on all loud moves)
+
+        for fen in PERFT_FENS {
+            let mut board = Board::from_fen(fen);
+            for r#move in board.loud_moves() {
+                let evaluation = board.see(r#move);
+                for threshold in [-1000, -200, -100, 0, 1, 100, 200, 1000] {
+                    assert_eq!(board.see_ge(r#move, threshold), evaluation >= threshold, "{fen}, {}", r#move.to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_late_move_parameters() {
+        // reductions grow with depth and move number but spare the first moves, and survive re-tuning
+
+        let mut parameters = SearchParameters::default();
+        for depth_left in 1..=u8::MAX {
+            assert_eq!(parameters.reduction(depth_left, 1), 0);
+            for n_moves in 2..100 {
+                assert!(parameters.reduction(depth_left, n_moves) >= parameters.reduction(depth_left, n_moves - 1));
+                assert!(parameters.reduction(depth_left, n_moves) >= parameters.reduction(depth_left - 1, n_moves));
+            }
+        }
+        assert!(parameters.reduction(12, 30) > 0);
+        assert!(!parameters.is_late_for_pruning(parameters.pruning_depth + 1, usize::MAX));
+
+        parameters.reduction_base = 0;
+        parameters.reduction_divisor = 100;
+        parameters.fill_reductions();
+        assert_eq!(parameters.reduction(12, 30), 8);
+
+        parameters.pruning_depth = 0;
+        assert!(!parameters.is_late_for_pruning(1, usize::MAX));
+    }
+
+    #[test]
+    fn test_late_move_history_multiple() {
+        // a move that caused a cutoff at the depth before is reduced less, at any depth
+
+        let parameters = SearchParameters::default();
+        for depth_left in 1..=u8::MAX {
+            for n_moves in 2..100 {
+                for is_pv_node in [false, true] {
+                    let reduction = parameters.late_move_reduction(depth_left, n_moves, is_pv_node, 0);
+                    let good_history = history_bonus(depth_left);
+                    assert_eq!(parameters.late_move_reduction(depth_left, n_moves, is_pv_node, good_history), reduction.saturating_sub(1));
+                    assert_eq!(parameters.late_move_reduction(depth_left, n_moves, is_pv_node, good_history - 1), reduction);
+                }
+            }
+        }
+        assert!(parameters.late_move_reduction(12, 30, false, history_bonus(12)) < parameters.late_move_reduction(12, 30, false, 0));
+
+        // quiet moves earn their history by causing cutoffs, like the first moves from the start position
+        let mut board = Board::default();
+        let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+        let info = alpha_beta(&mut board, ASPIRATION_DEPTH, &[], &parameters, &transposition_table);
+        assert!(board.get_legal_moves().iter().any(|r#move|
+            info.history_heuristic[r#move.moving_piece_as_index()][r#move.to_square_as_index()] > 0
+        ));
+    }
+
+    #[test]
+    fn test_heuristic_cutoffs_multiple() {
+        // every quiet cutoff is credited to one move ordering heuristic at most
+
+        for fen in PERFT_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let info = alpha_beta(&mut board, ASPIRATION_DEPTH, &[], &SearchParameters::default(), &transposition_table);
+            info.visualize();
+
+            let heuristics = [info.killer_cutoffs, info.countermove_cutoffs, info.history_cutoffs];
+            assert!(heuristics.iter().all(|cutoffs| cutoffs.on_first_move <= cutoffs.total));
+            let credited: usize = heuristics.iter().map(|cutoffs| cutoffs.total).sum();
+            assert!(credited <= info.n_alpha_cutoffs + info.n_beta_cutoffs);
+        }
+    }
+
+    #[test]
+    fn test_aspiration_multiple() {
+        // aspiration windows around the previous depth's evaluation arrive at the full window's evaluation
+
+        // late move pruning is window dependent: a move's null window search and its re-search with the
+        // full window prune differently, and whether it's re-searched depends on the window
+        let mut parameters = SearchParameters::default();
+        parameters.pruning_depth = 0;
+        for fen in ASPIRATION_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let previous_evaluation = alpha_beta(&mut board, ASPIRATION_DEPTH - 1, &[], &parameters, &transposition_table).evaluation;
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let full_window = alpha_beta(&mut board, ASPIRATION_DEPTH, &[], &parameters, &transposition_table);
+            let (evaluation, nodes) = (full_window.evaluation, full_window.nodes_visited);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let aspiration = alpha_beta_with_aspiration(
+                &mut board, ASPIRATION_DEPTH, previous_evaluation, &[], &parameters, &transposition_table, |_| {}
+            );
+            aspiration.visualize();
+
+            println!("Full window: {evaluation} in {nodes} nodes, aspiration: {} in {} nodes", aspiration.evaluation, aspiration.nodes_visited);
+            assert_eq!(aspiration.evaluation, evaluation);
+        }
+    }
+
+    #[test]
+    fn test_aspiration_default_parameters_multiple() {
+        // with all late move heuristics, aspiration windows stay close to the full window, and find the mates
+
+        let parameters = SearchParameters::default();
+        for fen in ASPIRATION_FENS {
+            println!("FEN: {fen}");
+            let mut board = Board::from_fen(fen);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let previous_evaluation = alpha_beta(&mut board, ASPIRATION_DEPTH - 1, &[], &parameters, &transposition_table).evaluation;
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let full_window = alpha_beta(&mut board, ASPIRATION_DEPTH, &[], &parameters, &transposition_table);
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let aspiration = alpha_beta_with_aspiration(
+                &mut board, ASPIRATION_DEPTH, previous_evaluation, &[], &parameters, &transposition_table, |_| {}
+            );
+
+            println!("Full window: {}, aspiration: {}", full_window.evaluation, aspiration.evaluation);
+            assert!(aspiration.evaluation.abs_diff(full_window.evaluation) <= ASPIRATION_TOLERANCE);
+        }
+
+        for entry in EpdEntry::read_all(MATE_EPD).expect("Mate suite should be valid") {
+            println!("FEN: {}, id: {}", entry.board.to_fen(), entry.id().unwrap());
+            let evaluation = if entry.board.whites_turn {1} else {-1} * entry.centipawn_evaluation().unwrap();
+            let mut board = entry.board.clone();
+
+            let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+            let previous_evaluation = alpha_beta(&mut board, MATE_SEARCH_DEPTH - 1, &[], &parameters, &transposition_table).evaluation;
+            let aspiration = alpha_beta_with_aspiration(
+                &mut board, MATE_SEARCH_DEPTH, previous_evaluation, &[], &parameters, &transposition_table, |_| {}
+            );
+            assert!(entry.is_solved_by(aspiration.best_move.unwrap(), aspiration.evaluation) && aspiration.evaluation == evaluation);
+        }
+    }
+
     /*#[test]
     fn compare_minimax_alpha_beta_multiple() {
         // Test whether minimax and alpha_beta return the same results
@@ -308,7 +1279,7 @@ mod tests {
             minimax(&mut board, COMPARE_DEPTH, &mut transposition_table);
 
             let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
-            alpha_beta(&mut board, COMPARE_DEPTH, &mut transposition_table).visualize();
+            alpha_beta(&mut board, COMPARE_DEPTH, &[], &SearchParameters::default(), &mut transposition_table).visualize();
         }
     }*/
 
@@ -341,7 +1312,11 @@ mod tests {
         for fen in PERFT_FENS {
             println!("FEN: {fen}\n");
 
-            perft(&mut Board::from_fen(fen), PERFT_DEPTH);
+            let now = Instant::now();
+            let info = Board::from_fen(fen).perft_info(PERFT_DEPTH);
+            info.visualize();
+            println!("\tDuration: {}ms\n", now.elapsed().as_millis());
         }
     }
 }
+
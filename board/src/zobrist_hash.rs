@@ -22,6 +22,12 @@ impl Default for ZobristHash {
     }
 }
 
+impl From<ZobristHash> for u64 {
+    fn from(zobrist_hash: ZobristHash) -> u64 {
+        zobrist_hash.0
+    }
+}
+
 impl ZobristHash {
     const fn u64_from_bytes(bytes: [u8; 8]) -> u64 {
         return 0
@@ -115,6 +121,44 @@ impl ZobristHash {
         keys
     };
 
+    // identifies this set of keys (drawn anew with every build), so that saved hashes of other builds are rejected
+    pub const KEY_SET_FINGERPRINT: u64 = {
+        const fn mix(fingerprint: u64, key: u64) -> u64 {
+            // one FNV-1a step per key, folding the high bits back in
+            let mixed = (fingerprint ^ key).wrapping_mul(0x100_0000_01b3);
+            mixed ^ mixed >> 29
+        }
+
+        let mut fingerprint: u64 = 0xcbf2_9ce4_8422_2325;
+        fingerprint = mix(fingerprint, Self::PLAYER_KEY);
+        fingerprint = mix(fingerprint, Self::WHITE_SHORT_KEY);
+        fingerprint = mix(fingerprint, Self::WHITE_LONG_KEY);
+        fingerprint = mix(fingerprint, Self::BLACK_SHORT_KEY);
+        fingerprint = mix(fingerprint, Self::BLACK_LONG_KEY);
+        let mut p = 0;
+        while p < 12 {
+            let mut i = 0;
+            while i < 64 {
+                fingerprint = mix(fingerprint, Self::PIECE_POSITION_KEYS[p][i]);
+                i += 1;
+            }
+            p += 1;
+        }
+        let mut i = 0;
+        while i < 64 {
+            fingerprint = mix(fingerprint, Self::EN_PASSANT_KEYS[i]);
+            i += 1;
+        }
+        let mut i = 0;
+        while i < 150 {
+            fingerprint = mix(fingerprint, Self::MOVE_COUNTER_KEYS[i]);
+            i += 1;
+        }
+
+        // return
+        fingerprint
+    };
+
     pub fn visualize(self: &Self) {
         // print a representation of the hash into console
         print!("{:#x}", self.0);
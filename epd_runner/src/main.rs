@@ -0,0 +1,164 @@
+/*
+Runs the search over an EPD test suite (WAC, STS, ...) and reports which positions it solves.
+
+usage: epd_runner <suite.epd> [--depth <plies>] [--movetime <ms>]
+    - without limits, every position is searched to depth 6
+    - with --movetime, iterative deepening runs until the time is up (or --depth is reached)
+
+TODO:
+    - reuse the UCI time management once it lives in the search crate
+*/
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use board::board::Board;
+use board::epd::EpdEntry;
+use board::moves::Move;
+use search::{clear_stop, emit_stop, query_stop};
+use search::alpha_beta::alpha_beta;
+use search::parameters::SearchParameters;
+use search::transposition_table::TranspositionTable;
+
+const DEFAULT_DEPTH: u8 = 6;
+
+
+struct Limits {
+    depth: Option<u8>,
+    movetime: Option<Duration>
+}
+
+
+fn parse_arguments() -> (String, Limits) {
+    let mut arguments = std::env::args().skip(1);
+    let mut path: Option<String> = None;
+    let mut limits = Limits {depth: None, movetime: None};
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--depth" => {
+                let depth = arguments.next().and_then(|d| d.parse().ok()).expect("--depth takes a number of plies!");
+                limits.depth = Some(depth);
+            },
+            "--movetime" => {
+                let movetime = arguments.next().and_then(|t| t.parse().ok()).expect("--movetime takes milliseconds!");
+                limits.movetime = Some(Duration::from_millis(movetime));
+            },
+            _ => path = Some(argument)
+        }
+    }
+
+    let path = path.expect("usage: epd_runner <suite.epd> [--depth <plies>] [--movetime <ms>]");
+    if limits.depth.is_none() && limits.movetime.is_none() {
+        limits.depth = Some(DEFAULT_DEPTH);
+    }
+    return (path, limits);
+}
+
+
+fn search(
+    board: &mut Board,
+    limits: &Limits,
+    transposition_table: &mut TranspositionTable<Board>
+) -> Option<(Move, i32, u8)> {
+    // iterative deepening within the limits, returns best move, evaluation and depth of the last full iteration
+
+    clear_stop();
+    transposition_table.new_search();
+
+    // stop the search once the time is up (unless it finished before)
+    let finished = Arc::new(AtomicBool::new(false));
+    let timer = limits.movetime.map(|movetime| {
+        let finished = finished.clone();
+        thread::spawn(move || {
+            let now = Instant::now();
+            while now.elapsed() < movetime {
+                if finished.load(Ordering::Relaxed) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            emit_stop();
+        })
+    });
+
+    let max_depth = limits.depth.unwrap_or(u8::MAX);
+    let parameters = SearchParameters::default();
+    let mut result = None;
+    for depth in 1..=max_depth {
+        let info = alpha_beta(board, depth, &[], &parameters, transposition_table);
+
+        // results of an interrupted iteration are incomplete
+        if query_stop() {
+            break;
+        }
+        result = info.best_move.map(|r#move| (r#move, info.evaluation, depth));
+    }
+
+    finished.store(true, Ordering::Relaxed);
+    if let Some(timer) = timer {
+        timer.join().expect("Timer thread panicked!");
+    }
+
+    return result;
+}
+
+
+fn main() {
+    let (path, limits) = parse_arguments();
+
+    let suite = std::fs::read_to_string(&path).expect("Couldn't read EPD file!");
+    let entries = match EpdEntry::read_all(&suite) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Invalid EPD file: {error}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut transposition_table: TranspositionTable<Board> = TranspositionTable::new();
+    let mut failed_ids: Vec<String> = Vec::new();
+    let start = Instant::now();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let id = entry.id().map(str::to_owned).unwrap_or(format!("#{}", index + 1));
+        let mut board = entry.board.clone();
+
+        let expected: Vec<String> = entry.best_moves.iter().map(|r#move| r#move.to_san(&entry.board)).collect();
+        let avoided: Vec<String> = entry.avoid_moves.iter().map(|r#move| r#move.to_san(&entry.board)).collect();
+
+        match search(&mut board, &limits, &mut transposition_table) {
+            None => {
+                println!("{id}: no move found");
+                failed_ids.push(id);
+            },
+            Some((best_move, evaluation, depth)) => {
+                let solved = entry.is_solved_by(best_move, evaluation);
+                println!(
+                    "{id}: {} {} (score {evaluation}, depth {depth}), bm [{}], am [{}]",
+                    if solved {"solved"} else {"FAILED"},
+                    best_move.to_san(&entry.board),
+                    expected.join(" "),
+                    avoided.join(" ")
+                );
+                if !solved {
+                    failed_ids.push(id);
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "solved: {}, failed: {}, total: {} ({:.1}s)",
+        entries.len() - failed_ids.len(),
+        failed_ids.len(),
+        entries.len(),
+        start.elapsed().as_secs_f32()
+    );
+    if !failed_ids.is_empty() {
+        println!("failed: {}", failed_ids.join(" "));
+    }
+}
@@ -5,7 +5,7 @@ use bitboards::{
     bitloop
 };
 
-use bytemuck::{bytes_of, allocation::zeroed_box};
+use bytemuck::allocation::zeroed_box;
 use std::{
     path::PathBuf,
     env::var_os,
@@ -488,54 +488,139 @@ fn get_path_without_end_masks() -> Box<[[Bitboard; 64]; 64]> {
     masks
 }
 
+struct Magics {
+    magics: Box<[Bitboard; 64]>,
+    shifts: [u8; 64],
+    attacks: Vec<Bitboard>  // the tables of all squares one after another, indexed by offset and magic index
+}
+
+fn find_magics<const N: usize>(pext_masks: &[Bitboard; 64], pext_attacks: &[[Bitboard; N]; 64]) -> Magics {
+    /*
+    for each square search a fancy magic number, i.e. a factor that maps every occupancy of the
+    PEXT mask to an index with as many bits as the mask, such that occupancies landing on the same
+    index have the same attacks (https://www.chessprogramming.org/Looking_for_Magics)
+    the random numbers are seeded, so every build finds the same magics
+    every square's table only takes as many entries as its index has values, the tables are packed
+    (the offset of a square is the sum of the sizes of the tables before, see magic_offsets in lib.rs)
+    */
+
+    let mut magics: Box<[Bitboard; 64]> = zeroed_box();
+    let mut shifts: [u8; 64] = [0; 64];
+    let mut attacks: Vec<Bitboard> = Vec::new();
+
+    // xorshift
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut random = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for square in 0..64 {
+        let mask = pext_masks[square];
+        let bits = mask.count_ones();
+        let shift = (64 - bits) as u8;
+
+        // all occupancies of the mask together with their attacks
+        let occupancies: Vec<(Bitboard, Bitboard)> = (0..1 << bits)
+            .map(|pext_occupancy| (Bitboard::const_pdep(pext_occupancy as u64, mask), pext_attacks[square][pext_occupancy]))
+            .collect();
+
+        let mut table: Vec<Option<Bitboard>> = vec![None; 1 << bits];
+        'search: loop {
+            // sparse candidates with enough bits landing in the index are much more likely to work
+            let magic = Bitboard(random() & random() & random());
+            if (mask.0.wrapping_mul(magic.0) >> 56).count_ones() < 6 {
+                continue;
+            }
+
+            table.fill(None);
+            for &(occupancy, attacks) in &occupancies {
+                let entry = &mut table[occupancy.magic_index(mask, magic, shift)];
+                match entry {
+                    None => *entry = Some(attacks),
+                    Some(other) if *other == attacks => {},
+                    Some(_) => continue 'search
+                }
+            }
+
+            magics[square] = magic;
+            shifts[square] = shift;
+            attacks.extend(table.iter().map(|entry| entry.unwrap_or(Bitboard(0))));
+            break;
+        }
+    }
+
+    Magics {magics, shifts, attacks}
+}
+
+fn to_target_bytes(bitboards: &[Bitboard]) -> Vec<u8> {
+    // serialize in the byte order of the target, which may differ from the one running this script
+    let big_endian = var_os("CARGO_CFG_TARGET_ENDIAN").expect("Cargo sets the target endianness") == "big";
+    bitboards.iter()
+        .flat_map(|bitboard| if big_endian {bitboard.0.to_be_bytes()} else {bitboard.0.to_le_bytes()})
+        .collect()
+}
+
 fn write_from_suffix(data: &[u8], suffix: &str) {
     let out_dir = PathBuf::from(var_os("OUT_DIR").unwrap());
     write(out_dir.join(suffix), data).expect("Writing failed!");
 }
 
 fn write_x_pext_masks() {
-    let mut container = zeroed_box::<[Bitboard; 64]>();
-    *container = X_PEXT_MASK;
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "x_pext_masks.bin");
+    let bytes = to_target_bytes(&X_PEXT_MASK);
+    write_from_suffix(&bytes, "x_pext_masks.bin");
 }
 
 fn write_plus_pext_masks() {
-    // write into container, cast to bytes and write to file
-    let mut container = zeroed_box::<[Bitboard; 64]>();
-    *container = PLUS_PEXT_MASK;
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "plus_pext_masks.bin");
+    // serialize and write to file
+    let bytes = to_target_bytes(&PLUS_PEXT_MASK);
+    write_from_suffix(&bytes, "plus_pext_masks.bin");
 }
 
 fn write_knight_masks() {
     let container = get_knight_masks();
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "knight_masks.bin");
+    let bytes = to_target_bytes(&*container);
+    write_from_suffix(&bytes, "knight_masks.bin");
 }
 
 fn write_bishop_masks() {
     let container = get_bishop_masks();
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "bishop_masks.bin");
+    let bytes = to_target_bytes(container.as_flattened());
+    write_from_suffix(&bytes, "bishop_masks.bin");
 }
 
 fn write_rook_masks() {
     let container = get_rook_masks();
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "rook_masks.bin");
+    let bytes = to_target_bytes(container.as_flattened());
+    write_from_suffix(&bytes, "rook_masks.bin");
+}
+
+fn write_bishop_magics() {
+    let magics = find_magics(&X_PEXT_MASK, &get_bishop_masks());
+    write_from_suffix(&to_target_bytes(&*magics.magics), "x_magics.bin");
+    write_from_suffix(&magics.shifts, "x_magic_shifts.bin");
+    write_from_suffix(&to_target_bytes(&magics.attacks), "bishop_magic_masks.bin");
+}
+
+fn write_rook_magics() {
+    let magics = find_magics(&PLUS_PEXT_MASK, &get_rook_masks());
+    write_from_suffix(&to_target_bytes(&*magics.magics), "plus_magics.bin");
+    write_from_suffix(&magics.shifts, "plus_magic_shifts.bin");
+    write_from_suffix(&to_target_bytes(&magics.attacks), "rook_magic_masks.bin");
 }
 
 fn write_king_masks() {
     let container = get_king_masks();
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "king_masks.bin");
+    let bytes = to_target_bytes(&*container);
+    write_from_suffix(&bytes, "king_masks.bin");
 }
 
 fn write_path_without_end_masks() {
     let container = get_path_without_end_masks();
-    let bytes = bytes_of(&*container);
-    write_from_suffix(bytes, "path_without_end_masks.bin");
+    let bytes = to_target_bytes(container.as_flattened());
+    write_from_suffix(&bytes, "path_without_end_masks.bin");
 }
 
 
@@ -545,6 +630,8 @@ fn main() {
     write_knight_masks();
     write_bishop_masks();
     write_rook_masks();
+    write_bishop_magics();
+    write_rook_magics();
     write_king_masks();
     write_path_without_end_masks();
 
@@ -12,9 +12,9 @@ macro_rules! include_lookup_1d {
         // transmute to target type
         /*
         SAFETY:
-        Bitboard is a #[repr(transparent)] wrapper around u64.
-        RESTRICTIONS:
-        This entire program is also specific to x86_64, and therefore to little-endian byte order.
+        Bitboard is a #[repr(transparent)] wrapper around u64, and every bit pattern is a valid u64.
+        build.rs writes each u64 in the byte order of the target (not of the machine running the
+        build), so this is correct on little- and big-endian targets alike.
          */
         let lookup: [Bitboard; $N] = unsafe { std::mem::transmute(*bytes) };
 
@@ -36,9 +36,9 @@ macro_rules! include_lookup_2d {
         // transmute to target type
         /*
         SAFETY:
-        Bitboard is a #[repr(transparent)] wrapper around u64.
-        RESTRICTIONS:
-        This entire program is also specific to x86_64, and therefore to little-endian byte order.
+        Bitboard is a #[repr(transparent)] wrapper around u64, and every bit pattern is a valid u64.
+        build.rs writes each u64 in the byte order of the target (not of the machine running the
+        build), so this is correct on little- and big-endian targets alike.
          */
         let lookup: [[Bitboard; $N]; $M] = unsafe { std::mem::transmute(*bytes) };
 
@@ -6,6 +6,7 @@ TODO:
 */
 
 pub mod byte_magic;
+pub mod sliders;
 
 use bitboards::Bitboard;
 use crate::byte_magic::{include_lookup_1d, include_lookup_2d};
@@ -15,5 +16,28 @@ pub static PLUS_PEXT_MASK: [Bitboard; 64] = include_lookup_1d!("plus_pext_masks.
 pub static KNIGHT_MASK: [Bitboard; 64] = include_lookup_1d!("knight_masks.bin", 64);
 pub static BISHOP_MASK: [[Bitboard; 512]; 64] = include_lookup_2d!("bishop_masks.bin", 512, 64);
 pub static ROOK_MASK: [[Bitboard; 4096]; 64] = include_lookup_2d!("rook_masks.bin", 4096, 64);
+pub static X_MAGIC: [Bitboard; 64] = include_lookup_1d!("x_magics.bin", 64);
+pub static PLUS_MAGIC: [Bitboard; 64] = include_lookup_1d!("plus_magics.bin", 64);
+pub static X_MAGIC_SHIFT: [u8; 64] = X_MAGIC_SHIFT_BYTES;
+pub static PLUS_MAGIC_SHIFT: [u8; 64] = PLUS_MAGIC_SHIFT_BYTES;
+pub static X_MAGIC_OFFSET: [usize; 64] = magic_offsets(&X_MAGIC_SHIFT_BYTES);
+pub static PLUS_MAGIC_OFFSET: [usize; 64] = magic_offsets(&PLUS_MAGIC_SHIFT_BYTES);
+pub static BISHOP_MAGIC_MASK: [Bitboard; 5248] = include_lookup_1d!("bishop_magic_masks.bin", 5248);  // packed by square
+pub static ROOK_MAGIC_MASK: [Bitboard; 102400] = include_lookup_1d!("rook_magic_masks.bin", 102400);  // packed by square
 pub static KING_MASK: [Bitboard; 64] = include_lookup_1d!("king_masks.bin", 64);
 pub static PATH_WITHOUT_END: [[Bitboard; 64]; 64] = include_lookup_2d!("path_without_end_masks.bin", 64, 64);
+
+const X_MAGIC_SHIFT_BYTES: [u8; 64] = *include_bytes!(concat!(env!("OUT_DIR"), "/x_magic_shifts.bin"));
+const PLUS_MAGIC_SHIFT_BYTES: [u8; 64] = *include_bytes!(concat!(env!("OUT_DIR"), "/plus_magic_shifts.bin"));
+
+
+const fn magic_offsets(shifts: &[u8; 64]) -> [usize; 64] {
+    // where the attacks of every square start in a packed magic table (a square's index has 64 - shift bits)
+    let mut offsets = [0; 64];
+    let mut square = 1;
+    while square < 64 {
+        offsets[square] = offsets[square - 1] + (1 << (64 - shifts[square - 1]));
+        square += 1;
+    }
+    offsets
+}
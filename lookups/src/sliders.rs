@@ -0,0 +1,99 @@
+/*
+Bishop and rook attacks for a given occupation. How the occupation is turned into an index is
+chosen at build time:
+    - BMI2 PEXT into BISHOP_MASK/ROOK_MASK, if the target has BMI2 (e.g. -C target-cpu=native)
+      and the "magic" feature is off (PEXT is microcoded and slow on AMD before Zen 3)
+    - fancy magic multiplication into BISHOP_MAGIC_MASK/ROOK_MAGIC_MASK otherwise (the tables of
+      all squares packed into one, each as large as its index needs, starting at X/PLUS_MAGIC_OFFSET)
+    - a software PEXT into BISHOP_MASK/ROOK_MASK with the "software_pext" feature
+All of them give the same attacks, see test_slider_backends in the board crate.
+*/
+
+use bitboards::Bitboard;
+
+use crate::{
+    X_PEXT_MASK,
+    PLUS_PEXT_MASK,
+    BISHOP_MASK,
+    ROOK_MASK,
+    X_MAGIC,
+    PLUS_MAGIC,
+    X_MAGIC_SHIFT,
+    PLUS_MAGIC_SHIFT,
+    X_MAGIC_OFFSET,
+    PLUS_MAGIC_OFFSET,
+    BISHOP_MAGIC_MASK,
+    ROOK_MAGIC_MASK
+};
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SliderBackend {
+    Pext,
+    Magic,
+    SoftwarePext
+}
+
+
+#[cfg(feature = "software_pext")]
+pub const SLIDER_BACKEND: SliderBackend = SliderBackend::SoftwarePext;
+
+#[cfg(all(
+    not(feature = "software_pext"),
+    not(feature = "magic"),
+    target_arch = "x86_64",
+    target_feature = "bmi2"
+))]
+pub const SLIDER_BACKEND: SliderBackend = SliderBackend::Pext;
+
+#[cfg(all(
+    not(feature = "software_pext"),
+    any(feature = "magic", not(all(target_arch = "x86_64", target_feature = "bmi2")))
+))]
+pub const SLIDER_BACKEND: SliderBackend = SliderBackend::Magic;
+
+
+#[inline(always)]
+pub fn bishop_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    // squares a bishop on the given square sees (including the first blocker in every direction)
+    match SLIDER_BACKEND {
+        SliderBackend::Pext => BISHOP_MASK[square][occupation.pext(X_PEXT_MASK[square])],
+        SliderBackend::Magic => magic_bishop_attacks(square, occupation),
+        SliderBackend::SoftwarePext => software_bishop_attacks(square, occupation)
+    }
+}
+
+
+#[inline(always)]
+pub fn rook_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    // squares a rook on the given square sees (including the first blocker in every direction)
+    match SLIDER_BACKEND {
+        SliderBackend::Pext => ROOK_MASK[square][occupation.pext(PLUS_PEXT_MASK[square])],
+        SliderBackend::Magic => magic_rook_attacks(square, occupation),
+        SliderBackend::SoftwarePext => software_rook_attacks(square, occupation)
+    }
+}
+
+
+#[inline(always)]
+pub fn magic_bishop_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    BISHOP_MAGIC_MASK[X_MAGIC_OFFSET[square] + occupation.magic_index(X_PEXT_MASK[square], X_MAGIC[square], X_MAGIC_SHIFT[square])]
+}
+
+
+#[inline(always)]
+pub fn magic_rook_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    ROOK_MAGIC_MASK[PLUS_MAGIC_OFFSET[square] + occupation.magic_index(PLUS_PEXT_MASK[square], PLUS_MAGIC[square], PLUS_MAGIC_SHIFT[square])]
+}
+
+
+#[inline(always)]
+pub fn software_bishop_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    BISHOP_MASK[square][occupation.software_pext(X_PEXT_MASK[square])]
+}
+
+
+#[inline(always)]
+pub fn software_rook_attacks(square: usize, occupation: Bitboard) -> Bitboard {
+    ROOK_MASK[square][occupation.software_pext(PLUS_PEXT_MASK[square])]
+}
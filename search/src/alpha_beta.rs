@@ -1,14 +1,30 @@
-
 use generic_magic::{Bool, False, True};
 
 use crate::optimizer_generics::{Maximizer, Minimizer, Optimizer};
 use crate::traits::{AlphaBetaSearchFunctionality, SearchableMove};
 use crate::{I32_NAN, query_stop};
-use crate::search_info::SearchInfo;
+use crate::search_info::{Bound, RootMove, SearchInfo};
 use crate::quiescence::quiescence;
-use crate::{MAX_QUIESCENCE_DEPTH, MATE_EVALUATION, STOP_CHECKING_PERIOD};
+use crate::{MAX_QUIESCENCE_DEPTH, MATE_EVALUATION, DRAW_EVALUATION, STOP_CHECKING_PERIOD};
 use crate::move_ordering::MoveList;
 use crate::transposition_table::TranspositionTable;
+use crate::parameters::SearchParameters;
+
+/*
+TODO:
+    - tune the aspiration window (half width and widening factor)
+*/
+
+const ASPIRATION_WINDOW: i32 = 25;  // initial half width around the previous evaluation
+const ASPIRATION_WIDENING: i32 = 4;  // factor the half width grows by on every fail
+const MAX_ASPIRATION_WINDOW: i32 = 1_000;  // beyond that fall back to a full window
+
+const NULL_MOVE_MIN_DEPTH: u8 = 3;  // shallower the null move search would drop straight into quiescence
+const NULL_MOVE_REDUCTION: u8 = 2;
+const NULL_MOVE_DEEP_DEPTH: u8 = 6;  // deeper than that the reduction grows
+const NULL_MOVE_DEEP_REDUCTION: u8 = 3;
+
+const LATE_MOVE_REDUCTION_MIN_DEPTH: u8 = 3;  // shallower a reduction would drop straight into quiescence
 
 
 pub fn alpha_beta<
@@ -16,110 +32,422 @@ pub fn alpha_beta<
 >(
     board: &mut Board,
     max_depth: u8,
-    transposition_table: &'a mut TranspositionTable<Board>
+    search_moves: &[Board::Move],
+    parameters: &SearchParameters,
+    transposition_table: &'a TranspositionTable<Board>
 ) -> SearchInfo<'a, Board> {
+    // search with a full window (only search_moves at the root, unless empty)
 
-    fn inner_alpha_beta<
-        'a,
-        O: Optimizer,
-        MaxDepth: Bool,
-        Board: AlphaBetaSearchFunctionality
-    >(
-        board: &mut Board,
-        mut alpha: i32,
-        mut beta: i32,
-        depth_left: u8,
-        distance_to_root: i32,
-        info: &mut SearchInfo<'a, Board>
-    ) -> i32 {
-
-        // query transposition table
-        let (
-            is_hit,
-            is_exact,
+    let mut info = SearchInfo::from_transposition_table_and_parameters(transposition_table, parameters.clone());
+    info.search_moves = search_moves.to_vec();
+    let now = std::time::Instant::now();
+    search_root(board, i32::MIN, i32::MAX, max_depth, &mut info);
+    info.time_spent_searching = now.elapsed().as_millis();
+
+    return info;
+}
+
+
+pub fn alpha_beta_with_aspiration<
+    'a, Board: AlphaBetaSearchFunctionality
+>(
+    board: &mut Board,
+    max_depth: u8,
+    previous_evaluation: i32,
+    search_moves: &[Board::Move],
+    parameters: &SearchParameters,
+    transposition_table: &'a TranspositionTable<Board>,
+    mut on_window_fail: impl FnMut(&SearchInfo<'a, Board>)
+) -> SearchInfo<'a, Board> {
+    /*
+    search with a narrow window around the evaluation of the previous iteration of iterative
+    deepening, widening the side that failed until the evaluation lies inside the window
+    (on_window_fail gets to see every failed window, e.g. to report the bound)
+    */
+
+    let mut info = SearchInfo::from_transposition_table_and_parameters(transposition_table, parameters.clone());
+    info.search_moves = search_moves.to_vec();
+    let now = std::time::Instant::now();
+
+    // mate scores jump between iterations, so search those with a full window right away
+    let mut half_width = if previous_evaluation.abs() > MATE_EVALUATION / 2 {i32::MAX} else {ASPIRATION_WINDOW};
+    let mut alpha = previous_evaluation.saturating_sub(half_width);
+    let mut beta = previous_evaluation.saturating_add(half_width);
+
+    loop {
+        let nodes_before = info.nodes_visited;
+        let evaluation = search_root(board, alpha, beta, max_depth, &mut info);
+
+        let is_full_window = alpha == i32::MIN && beta == i32::MAX;
+        if query_stop() || is_full_window || (alpha < evaluation && evaluation < beta) {
+            break;
+        }
+
+        // the evaluation is only a bound, so widen the window on the side that failed and search again
+        on_window_fail(&info);
+        info.nodes_in_failed_aspirations += info.nodes_visited - nodes_before;
+        half_width = if half_width >= MAX_ASPIRATION_WINDOW {i32::MAX} else {half_width * ASPIRATION_WIDENING};
+        if evaluation <= alpha {
+            info.n_aspiration_fails_low += 1;
+            alpha = evaluation.saturating_sub(half_width);
+        } else {
+            info.n_aspiration_fails_high += 1;
+            beta = evaluation.saturating_add(half_width);
+        }
+    }
+
+    info.time_spent_searching = now.elapsed().as_millis();
+
+    return info;
+}
+
+
+pub fn alpha_beta_multi_pv<
+    'a, Board: AlphaBetaSearchFunctionality
+>(
+    board: &mut Board,
+    max_depth: u8,
+    n_lines: usize,
+    search_moves: &[Board::Move],
+    parameters: &SearchParameters,
+    transposition_table: &'a TranspositionTable<Board>
+) -> SearchInfo<'a, Board> {
+    /*
+    search the best n_lines root moves with a full window each, excluding the moves of the lines
+    found before at the root. Evaluation, best move and principal variation are the best line's
+    (which isn't always the first found, as later lines profit from the transposition table)
+    */
+
+    let mut info = SearchInfo::from_transposition_table_and_parameters(transposition_table, parameters.clone());
+    info.search_moves = search_moves.to_vec();
+    let now = std::time::Instant::now();
+
+    let mut lines: Vec<RootMove<Board::Move>> = Vec::with_capacity(n_lines);
+    while lines.len() < n_lines {
+        info.best_move = None;
+        search_root(board, i32::MIN, i32::MAX, max_depth, &mut info);
+
+        // stop when interrupted or out of moves
+        if query_stop() || info.best_move.is_none() {
+            break;
+        }
+        let line = info.root_moves[0].clone();
+        info.excluded_root_moves.push(line.r#move);
+        lines.push(line);
+    }
+
+    info.excluded_root_moves.clear();
+    if board.is_whites_turn() {
+        lines.sort_by_key(|line| std::cmp::Reverse(line.evaluation));
+    } else {
+        lines.sort_by_key(|line| line.evaluation);
+    }
+    if let Some(best_line) = lines.first() {
+        info.evaluation = best_line.evaluation;
+        info.bound = best_line.bound;
+        info.best_move = Some(best_line.r#move);
+        info.principal_variation = best_line.principal_variation.clone();
+    }
+    info.root_moves = lines;
+    info.time_spent_searching = now.elapsed().as_millis();
+
+    return info;
+}
+
+
+pub fn mate_search<
+    'a, Board: AlphaBetaSearchFunctionality
+>(
+    board: &mut Board,
+    max_depth: u8,
+    n_moves: u8,
+    search_moves: &[Board::Move],
+    parameters: &SearchParameters,
+    transposition_table: &'a TranspositionTable<Board>
+) -> SearchInfo<'a, Board> {
+    /*
+    search for a forced mate by the side to move in at most n_moves moves: the window only admits
+    mates that fast, so everything else fails low (high for the minimizer) early. The mate is
+    proven once the evaluation is exact
+    */
+
+    let n_plies = (2 * n_moves as i32 - 1).max(1);
+    let slowest_mate = MATE_EVALUATION - n_plies;
+    let (alpha, beta) = if board.is_whites_turn() {
+        (slowest_mate - 1, i32::MAX)
+    } else {
+        (i32::MIN, -slowest_mate + 1)
+    };
+
+    let mut info = SearchInfo::from_transposition_table_and_parameters(transposition_table, parameters.clone());
+    info.search_moves = search_moves.to_vec();
+    let now = std::time::Instant::now();
+    search_root(board, alpha, beta, max_depth, &mut info);
+    info.time_spent_searching = now.elapsed().as_millis();
+
+    return info;
+}
+
+
+fn search_root<
+    Board: AlphaBetaSearchFunctionality
+>(board: &mut Board, alpha: i32, beta: i32, max_depth: u8, info: &mut SearchInfo<Board>) -> i32 {
+    let evaluation = match board.is_whites_turn() {
+        false => inner_alpha_beta::<Minimizer, True, Board>(board, alpha, beta, max_depth, 0, info),
+        true  => inner_alpha_beta::<Maximizer, True, Board>(board, alpha, beta, max_depth, 0, info),
+    };
+
+    // (fail-hard, so the bounds are hit exactly)
+    info.bound = if evaluation <= alpha {
+        Bound::Upper
+    } else if evaluation >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+
+    if let Some(best_move) = info.best_move {
+        info.root_moves = vec![RootMove{
+            r#move: best_move,
             evaluation,
-            maybe_pv_move
-        ) = info.transposition_table.query::<
-            False  // CalledInQuiescence: Bool
-        >(board, alpha, beta, depth_left, distance_to_root);
+            bound: info.bound,
+            principal_variation: info.principal_variation.clone()
+        }];
+    }
 
-        if is_hit {
-            if MaxDepth::AS_BOOL {
-                if is_exact {
+    return evaluation;
+}
 
-                    info.n_transposition_hits += 1;
-                    info.thereof_exact += 1;
 
-                    info.evaluation = evaluation;
-                    info.best_move = maybe_pv_move;
+fn inner_alpha_beta<
+    'a,
+    O: Optimizer,
+    MaxDepth: Bool,
+    Board: AlphaBetaSearchFunctionality
+>(
+    board: &mut Board,
+    mut alpha: i32,
+    mut beta: i32,
+    depth_left: u8,
+    distance_to_root: i32,
+    info: &mut SearchInfo<'a, Board>
+) -> i32 {
+
+    let ply = distance_to_root as usize;
+    info.clear_pv(ply);
+
+    // repetitions, 50 move rule and dead positions are draws (but the root has to produce a move)
+    if !MaxDepth::AS_BOOL && board.is_draw() {
+        info.leaves_evaluated += 1;
+        return DRAW_EVALUATION;
+    }
 
-                    return evaluation;
-                }
-            }
+    // query transposition table
+    let (
+        is_hit,
+        is_exact,
+        evaluation,
+        maybe_pv_move
+    ) = info.transposition_table.query::<
+        False  // CalledInQuiescence: Bool
+    >(board, alpha, beta, depth_left, distance_to_root);
+
+    /*
+    only cut off outside the principal variation, which is searched to know it move by move (and
+    the root, always part of it, has to produce a move), there the entry only orders moves
+    */
+    let is_pv_node = beta.saturating_sub(alpha) > 1;
+    if is_hit && !MaxDepth::AS_BOOL && !is_pv_node {
+        info.n_transposition_hits += 1;
+
+        if is_exact {
+            info.thereof_exact += 1;
+        }
 
-            info.n_transposition_hits += 1;
+        return evaluation;
+    }
+
+    // base case
+    if depth_left == 0 {
+        return quiescence::<O, Board>(
+            board, alpha, beta, MAX_QUIESCENCE_DEPTH, distance_to_root, info
+        );
+    }
+
+    /*
+    null-move pruning: if passing the turn still fails high (low for the minimizer) at reduced
+    depth, a real move will too. Only outside the principal variation, not twice in a row, not in
+    check (passing would be illegal) and not with king and pawns only (zugzwang is common there)
+    */
+    if !MaxDepth::AS_BOOL && !is_pv_node && depth_left >= NULL_MOVE_MIN_DEPTH
+        && !board.last_move_was_null() && board.has_non_pawn_material() {
+
+        let static_evaluation = board.evaluate();
+        let is_promising = if O::IS_MAXIMIZER {static_evaluation >= beta} else {static_evaluation <= alpha};
+        if is_promising && beta.abs() < MATE_EVALUATION / 2 && alpha.abs() < MATE_EVALUATION / 2 && !board.is_check() {
+            info.n_null_move_searches += 1;
+
+            let reduction = if depth_left > NULL_MOVE_DEEP_DEPTH {NULL_MOVE_DEEP_REDUCTION} else {NULL_MOVE_REDUCTION};
+            let (null_alpha, null_beta) = if O::IS_MAXIMIZER {(beta - 1, beta)} else {(alpha, alpha + 1)};
+
+            board.make_null_move();
+            let null_move_evaluation = inner_alpha_beta::<
+                O::Opposite, False, Board
+            >(board, null_alpha, null_beta, depth_left.saturating_sub(1 + reduction), distance_to_root+1, info);
+            board.unmake_null_move();
 
-            if is_exact {
-                info.thereof_exact += 1;
+            if query_stop() {
+                return I32_NAN;
             }
 
-            return evaluation;
+            // cutoff (without trusting mate scores, which can be artifacts of passing)
+            if O::IS_MAXIMIZER && null_move_evaluation >= beta {
+                info.n_null_move_cutoffs += 1;
+                return beta;
+            } else if !O::IS_MAXIMIZER && null_move_evaluation <= alpha {
+                info.n_null_move_cutoffs += 1;
+                return alpha;
+            }
         }
+    }
 
-        // base case
-        if depth_left == 0 {
-            return quiescence::<O, Board>(
-                board, alpha, beta, MAX_QUIESCENCE_DEPTH, distance_to_root, info
-            );
+    // get legal moves (a null move has no countermove)
+    let maybe_last_move = board.last_move();
+    let maybe_countermove = maybe_last_move.and_then(|last_move|
+        info.countermoves[last_move.moving_piece_as_index()][last_move.to_square_as_index()]
+    );
+    let legal_moves = MoveList::new::<
+        False/*OnlyLoud*/, MaxDepth::Not/*HasLastMove*/, Board
+    >(
+        board, board.legal_moves(), maybe_pv_move, maybe_last_move,
+        info.killer_moves[ply], maybe_countermove, &info.history_heuristic
+    );
+
+    // the window we were called with, to tell exact evaluations from bounds in the end
+    let (original_alpha, original_beta) = (alpha, beta);
+    let is_in_check = board.is_check();
+
+    // a root searched without some of its moves mustn't be stored as if it had all of them
+    let is_root_restricted = MaxDepth::AS_BOOL && info.is_root_restricted();
+
+    // recurse children
+    let mut n_moves: usize = 0;
+    let mut best_evaluation: i32 = if O::IS_MAXIMIZER {i32::MIN} else {i32::MAX};
+    let mut best_move: Option<Board::Move> = None;
+    for r#move in legal_moves {
+        if MaxDepth::AS_BOOL && !info.is_searched_at_root(r#move) {
+            continue;
         }
+        n_moves += 1;
 
-        // get legal moves
-        let legal_moves = MoveList::new::<
-            False/*OnlyLoud*/, MaxDepth::Not/*HasLastMove*/
-        >(board.legal_moves(), maybe_pv_move, board.last_move(), &info.history_heuristic);
-
-        // recurse children
-        let mut n_moves: usize = 0;
-        let mut best_evaluation: i32 = if O::IS_MAXIMIZER {i32::MIN} else {i32::MAX};
-        let mut best_move: Option<Board::Move> = None;
-        for r#move in legal_moves {
-            n_moves += 1;
-
-            // find evaluation of child
-            board.make_move(r#move);
-            let child_evaluation = inner_alpha_beta::<
-                O::Opposite, False, Board
-            >(board, alpha, beta, depth_left-1, distance_to_root+1, info);
+        board.make_move(r#move);
+
+        /*
+        late moves: quiet moves the ordering put behind the first one rarely turn out best, so they
+        are searched at reduced depth (less so in the principal variation or with a good history)
+        and skipped altogether near the leaves outside of the principal variation. Never while in
+        check or giving check, where quiet moves are forced or tactical
+        */
+        let is_late_quiet_move = !MaxDepth::AS_BOOL && n_moves > 1 && !r#move.is_loud()
+            && !is_in_check && !board.is_check();
+
+        if is_late_quiet_move && !is_pv_node && best_evaluation.saturating_abs() < MATE_EVALUATION / 2
+            && info.parameters.is_late_for_pruning(depth_left, n_moves) {
             board.unmake_move();
+            info.n_late_move_prunings += 1;
+            continue;
+        }
+
+        let mut reduction: u8 = 0;
+        if is_late_quiet_move && depth_left >= LATE_MOVE_REDUCTION_MIN_DEPTH {
+            let history = info.history_heuristic[r#move.moving_piece_as_index()][r#move.to_square_as_index()];
+            reduction = info.parameters.late_move_reduction(depth_left, n_moves, is_pv_node, history);
+            reduction = reduction.min(depth_left - 2);  // don't reduce into quiescence
+        }
 
-            // check if search should stop
-            if info.nodes_visited % STOP_CHECKING_PERIOD == 0 {
-                if query_stop() {
-                    return I32_NAN;
+        /*
+        find evaluation of child via principal variation search: the first move is assumed to be
+        best (the move ordering put the PV move first), so the others only need to be proven worse
+        with a null window, and are searched again with the full window if that fails
+        */
+        let child_evaluation = if n_moves == 1 {
+            inner_alpha_beta::<
+                O::Opposite, False, Board
+            >(board, alpha, beta, depth_left-1, distance_to_root+1, info)
+        } else {
+            info.n_null_window_searches += 1;
+            let (null_alpha, null_beta) = if O::IS_MAXIMIZER {
+                (alpha, alpha.saturating_add(1))
+            } else {
+                (beta.saturating_sub(1), beta)
+            };
+
+            let mut null_window_evaluation = inner_alpha_beta::<
+                O::Opposite, False, Board
+            >(board, null_alpha, null_beta, depth_left-1-reduction, distance_to_root+1, info);
+
+            // a reduced move that fails high (low for the minimizer) has to prove itself at full depth
+            let fails_high = if O::IS_MAXIMIZER {null_window_evaluation > alpha} else {null_window_evaluation < beta};
+            if reduction > 0 {
+                info.n_late_move_reductions += 1;
+                if fails_high {
+                    info.n_late_move_re_searches += 1;
+                    null_window_evaluation = inner_alpha_beta::<
+                        O::Opposite, False, Board
+                    >(board, null_alpha, null_beta, depth_left-1, distance_to_root+1, info);
                 }
             }
 
-            if O::compare(best_evaluation, child_evaluation) {
-                best_evaluation = child_evaluation;
-                best_move = Some(r#move);
-                if MaxDepth::AS_BOOL {
-                    info.evaluation = child_evaluation;
-                    info.best_move = Some(r#move);
-                }
+            if alpha < null_window_evaluation && null_window_evaluation < beta {
+                // the move might be better after all, find out by how much
+                info.n_pvs_re_searches += 1;
+                let nodes_before = info.nodes_visited;
+                let child_evaluation = inner_alpha_beta::<
+                    O::Opposite, False, Board
+                >(board, alpha, beta, depth_left-1, distance_to_root+1, info);
+                info.nodes_in_pvs_re_searches += info.nodes_visited - nodes_before;
+                child_evaluation
+            } else {
+                null_window_evaluation
             }
+        };
+        board.unmake_move();
 
+        // check if search should stop
+        if info.nodes_visited % STOP_CHECKING_PERIOD == 0 {
+            if query_stop() {
+                return I32_NAN;
+            }
+        }
 
-            // update alpha/beta
-            if O::IS_MAXIMIZER {
-                alpha = O::compare_for_assign(alpha, best_evaluation);
-            } else {
-                beta = O::compare_for_assign(beta, best_evaluation);
+        if O::compare(best_evaluation, child_evaluation) {
+            best_evaluation = child_evaluation;
+            best_move = Some(r#move);
+
+            // a move inside the window continues the principal variation (at the root the best move does)
+            if MaxDepth::AS_BOOL || (alpha < child_evaluation && child_evaluation < beta) {
+                info.update_pv(ply, r#move);
+            }
+            if MaxDepth::AS_BOOL {
+                info.evaluation = child_evaluation;
+                info.best_move = Some(r#move);
+                info.principal_variation = info.pv(ply).to_vec();
             }
+        }
+
+
+        // update alpha/beta
+        if O::IS_MAXIMIZER {
+            alpha = O::compare_for_assign(alpha, best_evaluation);
+        } else {
+            beta = O::compare_for_assign(beta, best_evaluation);
+        }
 
-            // cutoff
-            if alpha >= beta {
+        // cutoff
+        if alpha >= beta {
 
-                // store in transposition table
+            // store in transposition table
+            if !is_root_restricted {
                 info.transposition_table.put::<
                     True,  // FromAlphaBeta: Bool
                     False  // FromQuiescence: Bool
@@ -128,78 +456,92 @@ pub fn alpha_beta<
                     false, !O::IS_MAXIMIZER, O::IS_MAXIMIZER,
                     Some(r#move),  // TODO: Don't remember cutoff move?
                 );
+            }
+
+            // remember cutoff
+            if O::IS_MAXIMIZER {
+                info.n_beta_cutoffs += 1;
+            } else {
+                info.n_alpha_cutoffs += 1;
+            }
 
-                // remember cutoff
+            // is the cutoff on first searched move?
+            if n_moves == 1 {
                 if O::IS_MAXIMIZER {
-                    info.n_beta_cutoffs += 1;
+                    info.betas_on_first_move += 1;
                 } else {
-                    info.n_alpha_cutoffs += 1;
+                    info.alphas_on_first_move += 1;
                 }
+            }
+
+            // update history heuristic (which orders the quiet moves and spares them reductions)
+            info.reward_history(r#move, depth_left);
+
+            // update killer moves and countermoves (loud moves are ordered early anyway)
+            if !r#move.is_loud() {
+                let is_killer = Some(r#move) == info.killer_moves[ply][0] || Some(r#move) == info.killer_moves[ply][1];
 
-                // is the cutoff on first searched move?
-                if n_moves == 1 {
-                    if O::IS_MAXIMIZER {
-                        info.betas_on_first_move += 1;
+                // credit the cutoff to the one heuristic that ordered the move (the first that applies)
+                if Some(r#move) != maybe_pv_move {
+                    let cutoffs = if is_killer {
+                        &mut info.killer_cutoffs
+                    } else if Some(r#move) == maybe_countermove {
+                        &mut info.countermove_cutoffs
                     } else {
-                        info.alphas_on_first_move += 1;
-                    }
+                        &mut info.history_cutoffs
+                    };
+                    cutoffs.count(n_moves == 1);
                 }
 
-                // update history heuristic
-                if r#move.is_loud() {
-                    info.history_heuristic
-                        [r#move.moving_piece_as_index()]
-                        [r#move.to_square_as_index()] += 2 << depth_left;
+                if !is_killer {
+                    info.killer_moves[ply][1] = info.killer_moves[ply][0];
+                    info.killer_moves[ply][0] = Some(r#move);
                 }
-
-                // do cutoff
-                return if O::IS_MAXIMIZER {
-                    beta  // beta-cutoff
-                } else {
-                    alpha  // alpha-cutoff
+                if let Some(last_move) = maybe_last_move {
+                    info.countermoves[last_move.moving_piece_as_index()][last_move.to_square_as_index()] = Some(r#move);
                 }
             }
+
+            // do cutoff
+            return if O::IS_MAXIMIZER {
+                beta  // beta-cutoff
+            } else {
+                alpha  // alpha-cutoff
+            }
         }
+    }
 
-        // count visited nodes
-        info.nodes_visited += n_moves;
+    // count visited nodes
+    info.nodes_visited += n_moves;
 
-        // check for terminal state, TODO: This should be stored in TT as well (same for quiescence)
-        if n_moves == 0 {
-            return if board.is_check() {
-                // checkmate
-                if O::IS_MAXIMIZER {
-                    -MATE_EVALUATION + distance_to_root
-                } else {
-                    MATE_EVALUATION - distance_to_root
-                }
+    // check for terminal state, TODO: This should be stored in TT as well (same for quiescence)
+    if n_moves == 0 {
+        return if board.is_check() {
+            // checkmate
+            if O::IS_MAXIMIZER {
+                -MATE_EVALUATION + distance_to_root
             } else {
-                // stalemate
-                0
+                MATE_EVALUATION - distance_to_root
             }
+        } else {
+            // stalemate
+            DRAW_EVALUATION
         }
+    }
 
-        // put in transposition table
+    // put in transposition table (if no move reached the window, the evaluation is only a bound)
+    let is_alpha_cut = O::IS_MAXIMIZER && best_evaluation <= original_alpha;
+    let is_beta_cut = !O::IS_MAXIMIZER && best_evaluation >= original_beta;
+    if !is_root_restricted {
         info.transposition_table.put::<
             True,  // FromAlphaBeta: Bool
             False  // FromQuiescence: Bool
         >(
             board, depth_left, distance_to_root,
-            best_evaluation, true, false, false,
+            best_evaluation, !is_alpha_cut && !is_beta_cut, is_alpha_cut, is_beta_cut,
             best_move
         );
-
-        return best_evaluation;
     }
 
-    // enter recursion and time
-    let mut info = SearchInfo::default_from_transposition_table(transposition_table);
-    let now = std::time::Instant::now();
-    match board.is_whites_turn() {
-        false => inner_alpha_beta::<Minimizer, True, Board>(board, i32::MIN, i32::MAX, max_depth, 0, &mut info),
-        true  => inner_alpha_beta::<Maximizer, True, Board>(board, i32::MIN, i32::MAX, max_depth, 0, &mut info),
-    };
-    info.time_spent_searching = now.elapsed().as_millis();
-
-    return info;
+    return best_evaluation;
 }
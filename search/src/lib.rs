@@ -15,12 +15,14 @@ mod optimizer_generics;
 pub mod minimax;
 mod quiescence;
 mod move_ordering;
+pub mod parameters;
 
 
 pub const I32_NAN: i32 = 0;
 const STOP_CHECKING_PERIOD: usize = 4096;
 const MAX_QUIESCENCE_DEPTH: u8 = 64;
-const MATE_EVALUATION: i32 = 30_000;
+pub const MATE_EVALUATION: i32 = 30_000;
+const DRAW_EVALUATION: i32 = 0;
 
 
 static STOP_BUFFER: AtomicBool = AtomicBool::new(false);
@@ -1,6 +1,6 @@
 
 use generic_magic::Bool;
-use crate::traits::SearchableMove;
+use crate::traits::{AlphaBetaSearchFunctionality, SearchableMove};
 
 const EAGER: bool = true;
 
@@ -8,6 +8,11 @@ const EAGER: bool = true;
 const PV_SCORE: i32 = 2000000;
 const RECAPTURE_SCORE: i32 = 2000000 - 1;
 const CAPTURE_SCORE: i32 = 2000000 - 2;
+const WINNING_LOUD_SCORE: i32 = 1500000;  // plus MVV-LVA, for loud moves that don't lose material
+const FIRST_KILLER_SCORE: i32 = 1000000;
+const SECOND_KILLER_SCORE: i32 = 1000000 - 1;
+const COUNTERMOVE_SCORE: i32 = 1000000 - 2;
+const LOSING_LOUD_SCORE: i32 = -1000000;  // plus MVV-LVA, after all quiet moves
 
 static MVV_LVA_SCORES: [[i32; 12]; 12] = {
     // for each pair (victim, attacker) of pieces the MVV-LVA score given by arr[victim][attacker]
@@ -49,10 +54,13 @@ pub struct MoveList<Move: SearchableMove>{
 
 
 impl<Move: SearchableMove> MoveList<Move> {
-    pub(crate) fn new<OnlyLoud: Bool, HasLastMove: Bool>(
+    pub(crate) fn new<OnlyLoud: Bool, HasLastMove: Bool, Board: AlphaBetaSearchFunctionality<Move = Move>>(
+        board: &Board,
         legal_moves: Vec<Move>,
         maybe_pv_move: Option<Move>,
         maybe_last_move: Option<Move>,
+        killer_moves: [Option<Move>; 2],
+        maybe_countermove: Option<Move>,
         history_heuristic: &[[i32; 64]; 12]
     ) -> Self {
 
@@ -61,10 +69,9 @@ impl<Move: SearchableMove> MoveList<Move> {
         // buffers for (re)capture heuristic
         let mut recapture_lva_index: Option<usize> = None;
         let mut recapture_lva_as_index: usize = usize::MAX;
-        let recapture_target = if HasLastMove::AS_BOOL {
-            maybe_last_move.unwrap().to_square_as_index()
-        } else {
-            usize::MAX  // invalid square!
+        let recapture_target = match maybe_last_move {
+            Some(last_move) if HasLastMove::AS_BOOL => last_move.to_square_as_index(),
+            _ => usize::MAX  // invalid square! (also after a null move, which has no target)
         };
 
         // pv move buffer
@@ -101,12 +108,23 @@ impl<Move: SearchableMove> MoveList<Move> {
                     score += MVV_LVA_SCORES[captured_piece][moving_piece];
                 }
 
+                // split by static exchange evaluation: winning captures first, losing ones last
+                score += if board.see_ge(r#move, 0) {WINNING_LOUD_SCORE} else {LOSING_LOUD_SCORE};
+
             }
 
             // quiet moves
             if !OnlyLoud::AS_BOOL && !r#move.is_loud() {  // TODO: This is the else-block of the above if
-                // handle history heuristic
-                score += history_heuristic[moving_piece][to_square];
+                // handle killer and countermove heuristics, the history heuristic orders the rest
+                score += if Some(r#move) == killer_moves[0] {
+                    FIRST_KILLER_SCORE
+                } else if Some(r#move) == killer_moves[1] {
+                    SECOND_KILLER_SCORE
+                } else if Some(r#move) == maybe_countermove {
+                    COUNTERMOVE_SCORE
+                } else {
+                    history_heuristic[moving_piece][to_square]
+                };
             }
 
             // push to pairs
@@ -0,0 +1,84 @@
+/*
+Tunable parameters of the search, settable by the GUI via UCI options.
+
+TODO:
+    - tune the defaults
+*/
+
+use crate::search_info::history_bonus;
+
+const MAX_REDUCTION_DEPTH: usize = 64;
+const MAX_REDUCTION_MOVES: usize = 64;
+
+
+#[derive(Clone)]
+pub struct SearchParameters {
+    // late move reductions: reduction(depth, n) = base + ln(depth) * ln(n) / divisor (in hundredths)
+    pub reduction_base: u32,
+    pub reduction_divisor: u32,
+
+    // late move pruning: at depth <= pruning_depth skip quiet moves after pruning_move_count + depth^2 moves
+    pub pruning_depth: u8,
+    pub pruning_move_count: usize,
+
+    reductions: [[u8; MAX_REDUCTION_MOVES]; MAX_REDUCTION_DEPTH]
+}
+
+
+impl Default for SearchParameters {
+    fn default() -> Self {
+        let mut parameters = Self{
+            reduction_base: 75,
+            reduction_divisor: 225,
+            pruning_depth: 3,
+            pruning_move_count: 3,
+            reductions: [[0; MAX_REDUCTION_MOVES]; MAX_REDUCTION_DEPTH]
+        };
+        parameters.fill_reductions();
+        return parameters;
+    }
+}
+
+
+impl SearchParameters {
+    pub fn fill_reductions(self: &mut Self) {
+        // recompute the reduction table, call after changing reduction_base or reduction_divisor
+        let base = self.reduction_base as f64 / 100.;
+        let divisor = self.reduction_divisor.max(1) as f64 / 100.;
+        for depth in 1..MAX_REDUCTION_DEPTH {
+            for n_moves in 1..MAX_REDUCTION_MOVES {
+                let reduction = base + (depth as f64).ln() * (n_moves as f64).ln() / divisor;
+                self.reductions[depth][n_moves] = reduction.clamp(0., u8::MAX as f64) as u8;
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn reduction(self: &Self, depth_left: u8, n_moves: usize) -> u8 {
+        // how much to reduce the n-th move (counting from 1) at the given depth
+        return self.reductions
+            [(depth_left as usize).min(MAX_REDUCTION_DEPTH - 1)]
+            [n_moves.min(MAX_REDUCTION_MOVES - 1)];
+    }
+
+    #[inline(always)]
+    pub fn late_move_reduction(self: &Self, depth_left: u8, n_moves: usize, is_pv_node: bool, history: i32) -> u8 {
+        // the reduction of a late quiet move, one less in the principal variation and one less if the
+        // move has caused a cutoff at this depth before
+        let mut reduction = self.reduction(depth_left, n_moves);
+        if is_pv_node {
+            reduction = reduction.saturating_sub(1);
+        }
+        if history >= history_bonus(depth_left) {
+            reduction = reduction.saturating_sub(1);
+        }
+        return reduction;
+    }
+
+    #[inline(always)]
+    pub fn is_late_for_pruning(self: &Self, depth_left: u8, n_moves: usize) -> bool {
+        // whether the n-th move (counting from 1) is a candidate for late move pruning
+        return depth_left <= self.pruning_depth
+            && n_moves > self.pruning_move_count + (depth_left as usize) * (depth_left as usize);
+    }
+}
@@ -1,7 +1,7 @@
 
 use generic_magic::{False, True};
 
-use crate::{I32_NAN, MAX_QUIESCENCE_DEPTH, query_stop, STOP_CHECKING_PERIOD};
+use crate::{DRAW_EVALUATION, I32_NAN, MAX_QUIESCENCE_DEPTH, query_stop, STOP_CHECKING_PERIOD};
 use crate::move_ordering::MoveList;
 use crate::optimizer_generics::Optimizer;
 use crate::search_info::SearchInfo;
@@ -19,13 +19,24 @@ pub(crate) fn quiescence<
     info: &mut SearchInfo<Board>
 ) -> i32 {
 
+    let ply = distance_to_root as usize;
+    info.clear_pv(ply);
+
+    // repetitions, 50 move rule and dead positions are draws
+    if board.is_draw() {
+        info.leaves_evaluated += 1;
+        return DRAW_EVALUATION;
+    }
+
     // probe transposition table
     let is_hit: bool;
     let is_exact: bool;
     let evaluation: i32;
     let mut maybe_pv_move = None;
 
-    if MAX_QUIESCENCE_DEPTH - depth_left < 4 {  // TODO: Very non-canonical
+    // (not in the principal variation, which is searched to know it move by move)
+    let is_pv_node = beta.saturating_sub(alpha) > 1;
+    if MAX_QUIESCENCE_DEPTH - depth_left < 4 && !is_pv_node {  // TODO: Very non-canonical
         (is_hit, is_exact, evaluation, maybe_pv_move) = info.transposition_table.query::<
             True  // CalledInQuiescence: Bool
         >(board, alpha, beta, depth_left, distance_to_root);
@@ -50,6 +61,9 @@ pub(crate) fn quiescence<
         return board.evaluate();
     }*/
 
+    // the window we were called with, to tell exact evaluations from bounds in the end
+    let (original_alpha, original_beta) = (alpha, beta);
+
     // standing pat / base case, TODO: Remember cuts? What should happen to the ofm counter?
     let standing_pat = board.evaluate();
     if O::IS_MAXIMIZER {
@@ -71,8 +85,9 @@ pub(crate) fn quiescence<
     }
 
     // get loud moves
-    let loud_moves = MoveList::new::<True/*OnlyLoud*/, True/*HasLastMove*/>(
-        board.loud_moves(), maybe_pv_move, board.last_move(), &info.history_heuristic
+    let loud_moves = board.loud_moves();
+    let loud_moves = MoveList::new::<True/*OnlyLoud*/, True/*HasLastMove*/, Board>(
+        board, loud_moves, maybe_pv_move, board.last_move(), [None; 2], None, &info.history_heuristic
     );
 
     // recurse children
@@ -80,6 +95,13 @@ pub(crate) fn quiescence<
     let mut best_evaluation: i32 = if O::IS_MAXIMIZER {i32::MIN} else {i32::MAX};
     let mut best_move: Option<Board::Move> = None;
     for r#move in loud_moves {
+
+        // captures that lose material (by static exchange evaluation) can't beat standing pat
+        if !board.see_ge(r#move, 0) {
+            info.n_losing_captures_pruned += 1;
+            continue;
+        }
+
         n_loud_moves += 1;
 
         // find evaluation of child
@@ -99,6 +121,11 @@ pub(crate) fn quiescence<
         if O::compare(best_evaluation, child_evaluation) {
             best_evaluation = child_evaluation;
             best_move = Some(r#move);
+
+            // a move inside the window continues the principal variation
+            if alpha < child_evaluation && child_evaluation < beta {
+                info.update_pv(ply, r#move);
+            }
         }
 
         // update alpha/beta
@@ -165,13 +192,15 @@ pub(crate) fn quiescence<
         return board.evaluate();  // TODO: Should this detect mates? If check we need to check other legal, non-loud, moves
     }
 
-    // put in transposition table
+    // put in transposition table (if no move reached the window, the evaluation is only a bound)
+    let is_alpha_cut = O::IS_MAXIMIZER && best_evaluation <= original_alpha;
+    let is_beta_cut = !O::IS_MAXIMIZER && best_evaluation >= original_beta;
     info.transposition_table.put::<
         False,  // FromAlphaBeta: Bool
         True  // FromQuiescence: Bool
     >(
         board, depth_left, distance_to_root,
-        best_evaluation, true, false, false,
+        best_evaluation, !is_alpha_cut && !is_beta_cut, is_alpha_cut, is_beta_cut,
         best_move
     );
 
@@ -1,13 +1,80 @@
-use crate::I32_NAN;
+use crate::{I32_NAN, MAX_QUIESCENCE_DEPTH};
+use crate::parameters::SearchParameters;
 use crate::traits::{AlphaBetaSearchFunctionality, SearchableMove};
 use crate::transposition_table::TranspositionTable;
 
+pub(crate) const MAX_KILLER_PLY: usize = 256;  // more plies than the depth (u8) allows
+const MAX_PV_PLY: usize = MAX_KILLER_PLY + MAX_QUIESCENCE_DEPTH as usize;  // alpha-beta and quiescence plies
+const MAX_HISTORY: i32 = 1 << 19;  // quiet moves stay ordered behind killers and countermoves
+const MAX_HISTORY_SHIFT: u8 = 16;  // deeper cutoffs don't weigh more
+
+
+#[inline(always)]
+pub fn history_bonus(depth_left: u8) -> i32 {
+    // what a cutoff with this depth left adds to the history of the move
+    return 2 << depth_left.min(MAX_HISTORY_SHIFT);
+}
+
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Bound {
+    Exact,
+    Lower,  // the evaluation failed high, the true one may be higher
+    Upper   // the evaluation failed low, the true one may be lower
+}
+
+
+impl Bound {
+    pub fn flipped(self: Self) -> Self {
+        // the bound from the other side's point of view
+        match self {
+            Bound::Exact => Bound::Exact,
+            Bound::Lower => Bound::Upper,
+            Bound::Upper => Bound::Lower
+        }
+    }
+}
+
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct HeuristicCutoffs {
+    // cutoffs by quiet moves a move ordering heuristic put where they were searched
+    pub total: usize,
+    pub on_first_move: usize
+}
+
+
+impl HeuristicCutoffs {
+    #[inline(always)]
+    pub(crate) fn count(self: &mut Self, is_first_move: bool) {
+        self.total += 1;
+        if is_first_move {
+            self.on_first_move += 1;
+        }
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct RootMove<Move> {
+    pub r#move: Move,
+    pub evaluation: i32,  // from white's point of view
+    pub bound: Bound,
+    pub principal_variation: Vec<Move>  // starting with the move
+}
+
 
 pub struct SearchInfo<'a, Board: AlphaBetaSearchFunctionality>{
     pub evaluation: i32,
+    pub bound: Bound,  // whether the evaluation (from white's point of view) fell outside the window
     pub best_move: Option<Board::Move>,
+    pub principal_variation: Vec<Board::Move>,  // starting with best_move, leading to the position evaluated
+    pub root_moves: Vec<RootMove<Board::Move>>,  // the lines found, best first (several with MultiPV)
+    pub excluded_root_moves: Vec<Board::Move>,  // not searched at the root
+    pub search_moves: Vec<Board::Move>,  // the only moves searched at the root, all if empty
 
-    pub transposition_table: &'a mut TranspositionTable<Board>,
+    pub transposition_table: &'a TranspositionTable<Board>,
+    pub parameters: SearchParameters,
 
     pub time_spent_searching: u128,
 
@@ -24,19 +91,59 @@ pub struct SearchInfo<'a, Board: AlphaBetaSearchFunctionality>{
     pub thereof_exact: usize,
     pub thereof_exact_in_quiescence: usize,
 
-    pub history_heuristic: [[i32; 64]; 12]
+    // cost of principal variation search and aspiration windows (compare nodes_visited to measure the gain)
+    pub n_null_window_searches: usize,
+    pub n_pvs_re_searches: usize,
+    pub nodes_in_pvs_re_searches: usize,
+    pub n_aspiration_fails_low: usize,
+    pub n_aspiration_fails_high: usize,
+    pub nodes_in_failed_aspirations: usize,
+
+    pub n_null_move_searches: usize,
+    pub n_null_move_cutoffs: usize,
+
+    pub n_late_move_reductions: usize,
+    pub n_late_move_re_searches: usize,
+    pub n_late_move_prunings: usize,
+
+    // cutoffs by a quiet move (but the PV move), by the heuristic that ordered it
+    pub killer_cutoffs: HeuristicCutoffs,
+    pub countermove_cutoffs: HeuristicCutoffs,
+    pub history_cutoffs: HeuristicCutoffs,
+
+    pub n_losing_captures_pruned: usize,  // in quiescence
+
+    pub history_heuristic: [[i32; 64]; 12],
+    pub killer_moves: [[Option<Board::Move>; 2]; MAX_KILLER_PLY],  // quiet moves that caused cutoffs, by distance to root
+    pub countermoves: [[Option<Board::Move>; 64]; 12],  // quiet moves that refuted a move, by its piece and to-square
+
+    // triangular PV table: the principal variation found so far from every ply on, by distance to root
+    pv_table: Vec<Vec<Board::Move>>
 
 }
 
 impl<'a, Board: AlphaBetaSearchFunctionality> SearchInfo<'a, Board> {
     pub fn default_from_transposition_table(
-        transposition_table: &'a mut TranspositionTable<Board>
+        transposition_table: &'a TranspositionTable<Board>
+    ) -> Self {
+        Self::from_transposition_table_and_parameters(transposition_table, SearchParameters::default())
+    }
+
+    pub fn from_transposition_table_and_parameters(
+        transposition_table: &'a TranspositionTable<Board>,
+        parameters: SearchParameters
     ) -> Self {
         Self{
             evaluation: I32_NAN,
+            bound: Bound::Exact,
             best_move: None,
+            principal_variation: Vec::new(),
+            root_moves: Vec::new(),
+            excluded_root_moves: Vec::new(),
+            search_moves: Vec::new(),
 
             transposition_table,
+            parameters,
 
             time_spent_searching: 0,
 
@@ -56,10 +163,71 @@ impl<'a, Board: AlphaBetaSearchFunctionality> SearchInfo<'a, Board> {
             thereof_exact: 0,
             thereof_exact_in_quiescence: 0,
 
-            history_heuristic: [[0; 64]; 12]
+            n_null_window_searches: 0,
+            n_pvs_re_searches: 0,
+            nodes_in_pvs_re_searches: 0,
+            n_aspiration_fails_low: 0,
+            n_aspiration_fails_high: 0,
+            nodes_in_failed_aspirations: 0,
+
+            n_null_move_searches: 0,
+            n_null_move_cutoffs: 0,
+
+            n_late_move_reductions: 0,
+            n_late_move_re_searches: 0,
+            n_late_move_prunings: 0,
+
+            killer_cutoffs: HeuristicCutoffs::default(),
+            countermove_cutoffs: HeuristicCutoffs::default(),
+            history_cutoffs: HeuristicCutoffs::default(),
+
+            n_losing_captures_pruned: 0,
+
+            history_heuristic: [[0; 64]; 12],
+            killer_moves: [[None; 2]; MAX_KILLER_PLY],
+            countermoves: [[None; 64]; 12],
+
+            pv_table: (0..=MAX_PV_PLY).map(|_| Vec::new()).collect()
         }
     }
 
+    #[inline(always)]
+    pub(crate) fn clear_pv(self: &mut Self, ply: usize) {
+        // a node starts without a principal variation
+        self.pv_table[ply].clear();
+    }
+
+    #[inline(always)]
+    pub(crate) fn update_pv(self: &mut Self, ply: usize, r#move: Board::Move) {
+        // the principal variation of the ply becomes the move followed by the one of the next ply
+        let (line, deeper_lines) = self.pv_table[ply..].split_first_mut().unwrap();
+        line.clear();
+        line.push(r#move);
+        line.extend_from_slice(&deeper_lines[0]);
+    }
+
+    #[inline(always)]
+    pub(crate) fn reward_history(self: &mut Self, r#move: Board::Move, depth_left: u8) {
+        // the move caused a cutoff, saturating so that the history never outgrows the move ordering
+        let history = &mut self.history_heuristic[r#move.moving_piece_as_index()][r#move.to_square_as_index()];
+        *history = i32::min(MAX_HISTORY, *history + history_bonus(depth_left));
+    }
+
+    #[inline(always)]
+    pub(crate) fn pv(self: &Self, ply: usize) -> &[Board::Move] {
+        return &self.pv_table[ply];
+    }
+
+    pub(crate) fn is_root_restricted(self: &Self) -> bool {
+        // whether the root is searched without some of its moves
+        return !self.excluded_root_moves.is_empty() || !self.search_moves.is_empty();
+    }
+
+    pub(crate) fn is_searched_at_root(self: &Self, r#move: Board::Move) -> bool {
+        return !self.excluded_root_moves.contains(&r#move)
+            && (self.search_moves.is_empty() || self.search_moves.contains(&r#move));
+    }
+
     pub fn visualize(self: &Self) where Board::Move: SearchableMove {
         print!(
             "\n\
@@ -72,6 +240,15 @@ impl<'a, Board: AlphaBetaSearchFunctionality> SearchInfo<'a, Board> {
             Transposition hits:\n\
             Total:         {}, thereof exact: {}\n\
             In Quiescence: {}, thereof exact: {}\n\
+            Principal variation search:\n\
+            \tnull windows {}, re-searched {}, nodes in re-searches {}\n\
+            Aspiration windows:\n\
+            \tfailed low {}, failed high {}, nodes in failed windows {}\n\
+            Null moves: {}, thereof cutoffs: {}\n\
+            Late moves:\n\
+            \treduced {}, re-searched {}, pruned {}\n\
+            Quiet cutoffs (ofm) by killer moves: {} ({}), by countermoves: {} ({}), by history: {} ({})\n\
+            Losing captures pruned in quiescence: {}\n\
             \n",
             self.evaluation, self.best_move.unwrap().to_string(),
             self.time_spent_searching,
@@ -79,7 +256,15 @@ impl<'a, Board: AlphaBetaSearchFunctionality> SearchInfo<'a, Board> {
             self.n_alpha_cutoffs, self.alphas_on_first_move, self.alphas_on_first_move as f32 / self.n_alpha_cutoffs as f32,
             self.n_beta_cutoffs, self.betas_on_first_move, self.betas_on_first_move as f32 / self.n_beta_cutoffs as f32,
             self.n_transposition_hits, self.thereof_exact,
-            self.n_transposition_hits_in_quiescence, self.thereof_exact_in_quiescence
+            self.n_transposition_hits_in_quiescence, self.thereof_exact_in_quiescence,
+            self.n_null_window_searches, self.n_pvs_re_searches, self.nodes_in_pvs_re_searches,
+            self.n_aspiration_fails_low, self.n_aspiration_fails_high, self.nodes_in_failed_aspirations,
+            self.n_null_move_searches, self.n_null_move_cutoffs,
+            self.n_late_move_reductions, self.n_late_move_re_searches, self.n_late_move_prunings,
+            self.killer_cutoffs.total, self.killer_cutoffs.on_first_move,
+            self.countermove_cutoffs.total, self.countermove_cutoffs.on_first_move,
+            self.history_cutoffs.total, self.history_cutoffs.on_first_move,
+            self.n_losing_captures_pruned
         );
     }
 }
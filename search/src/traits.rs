@@ -15,23 +15,41 @@ pub trait SearchableMove: Copy + Clone + PartialEq {
     fn to_square_as_index(self: &Self) -> usize;
     fn moving_piece_as_index(self: &Self) -> usize;
     fn captured_piece_as_index(self: &Self) -> usize;
+
+    // for the transposition table, 0 is reserved for "no move"
+    fn to_bits(self: &Self) -> u32;
+    fn from_bits(bits: u32) -> Self;
 }
 
 
 pub trait AlphaBetaSearchFunctionality {
     type Move: SearchableMove;
-    type ZobristHash: Eq + Copy;
+    type ZobristHash: Eq + Copy + Into<u64>;
+
+    // identifies the zobrist keys, transposition tables saved with other keys are useless
+    const ZOBRIST_KEY_SET: u64;
 
     fn is_whites_turn(self: &Self) -> bool;
     fn make_move(self: &mut Self, r#move: Self::Move);
     fn unmake_move(self: &mut Self);
     fn evaluate(self: &Self) -> i32;
     fn is_check(self: &Self) -> bool;
+    fn is_draw(self: &Self) -> bool;
     fn zobrist_hash(self: &Self) -> Self::ZobristHash;
     fn legal_moves(self: &Self) -> Vec<Self::Move>;
 
     fn loud_moves(self: &mut Self) -> Vec<Self::Move>;
-    fn last_move(self: &Self) -> Option<Self::Move>;
+    fn last_move(self: &Self) -> Option<Self::Move>;  // None at the root and after a null move
+
+    // for null-move pruning
+    fn make_null_move(self: &mut Self);
+    fn unmake_null_move(self: &mut Self);
+    fn last_move_was_null(self: &Self) -> bool;
+    fn has_non_pawn_material(self: &Self) -> bool;
+
+    // static exchange evaluation, for move ordering and pruning of captures
+    fn see(self: &Self, r#move: Self::Move) -> i32;
+    fn see_ge(self: &Self, r#move: Self::Move, threshold: i32) -> bool;
 }
 
 /*pub trait MCTSFunctionality {
@@ -1,86 +1,345 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
 use generic_magic::Bool;
 use crate::{I32_NAN, MATE_EVALUATION};
 
-use crate::traits::AlphaBetaSearchFunctionality;
+use crate::traits::{AlphaBetaSearchFunctionality, SearchableMove};
 
 /*
 TODO:
     - handle querying here? (What did I mean by this???)
-    - remove old entries?
+    - tune the age penalty
+    - fixed zobrist keys would keep saved tables valid across builds
 */
 
-
-struct TranspositionTableEntry<Board: AlphaBetaSearchFunctionality> {
-    pub zobrist_hash: Board::ZobristHash,
+const BUCKET_SIZE: usize = 4;  // entries sharing a cache line
+const DEPTH_PREFERRED: usize = 3;  // the first entries of a bucket keep the deepest results, the last is always replaced
+const AGE_PENALTY: i32 = 4;  // an entry of the previous search is worth as much as one this many plies shallower
+const HASHFULL_SAMPLE: usize = 1000;  // entries looked at to estimate the fill level
+
+// saved tables start with a header, followed by every entry as key and data (all little endian):
+// |magic   |version|entry size|bucket size|generation|capacity (in entries)|zobrist key set|
+// |8 bytes |u32    |u32       |u32        |u32       |u64                  |u64            |
+const FILE_MAGIC: [u8; 8] = *b"EMET-TT\0";
+const FILE_VERSION: u32 = 1;  // bump whenever the header or the packing of entries changes
+const FILE_HEADER_SIZE: u64 = 8 + 4 * 4 + 8 + 8;  // in bytes
+const FILE_ENTRY_SIZE: u64 = 2 * 8;  // key and data, in bytes
+
+// represent a whole entry (but the hash) as one number:
+// |gen|flags |depth   |evaluation       |move                             |
+// |000|0 0000|00000000|00000000 00000000|00000000 00000000 00000000 00000000|
+//-> 32 bits: move (0 if none)      shift: 0
+//-> 16 bits: evaluation (as i16)   shift: 32
+//-> 8 bits: depth left             shift: 48
+//-> 1 bit: is exact?               mask: 1 << 56
+//-> 1 bit: is alpha cut?           mask: 1 << 57
+//-> 1 bit: is beta cut?            mask: 1 << 58
+//-> 1 bit: from quiescence?        mask: 1 << 59
+//-> 1 bit: is occupied?            mask: 1 << 60
+//-> 3 bits: generation             shift: 61
+const EVALUATION_SHIFT: u64 = 32;
+const DEPTH_LEFT_SHIFT: u64 = 48;
+const IS_EXACT_MASK: u64 = 1 << 56;
+const IS_ALPHA_CUT_MASK: u64 = 1 << 57;
+const IS_BETA_CUT_MASK: u64 = 1 << 58;
+const FROM_QUIESCENCE_MASK: u64 = 1 << 59;
+const IS_OCCUPIED_MASK: u64 = 1 << 60;
+const GENERATION_SHIFT: u64 = 61;
+const GENERATION_MASK: u8 = 0b111;
+
+
+struct TranspositionTableEntry<Move: SearchableMove> {
     pub depth_left: u8,
     pub evaluation: i32,
     pub is_exact: bool,
     pub is_alpha_cut: bool,
     pub is_beta_cut: bool,
-    pub maybe_pv_move: Option<Board::Move>
+    pub from_quiescence: bool,
+    pub generation: u8,  // of the search that stored the entry
+    pub maybe_pv_move: Option<Move>
+}
+
+
+impl<Move: SearchableMove> TranspositionTableEntry<Move> {
+    fn pack(self: &Self) -> u64 {
+        // evaluations beyond i16 only occur as bounds, which stay valid (if weaker) when clamped
+        let evaluation = self.evaluation.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        return self.maybe_pv_move.map_or(0, |r#move| r#move.to_bits()) as u64
+            | (evaluation as u16 as u64) << EVALUATION_SHIFT
+            | (self.depth_left as u64) << DEPTH_LEFT_SHIFT
+            | if self.is_exact {IS_EXACT_MASK} else {0}
+            | if self.is_alpha_cut {IS_ALPHA_CUT_MASK} else {0}
+            | if self.is_beta_cut {IS_BETA_CUT_MASK} else {0}
+            | if self.from_quiescence {FROM_QUIESCENCE_MASK} else {0}
+            | IS_OCCUPIED_MASK
+            | ((self.generation & GENERATION_MASK) as u64) << GENERATION_SHIFT;
+    }
+
+    fn unpack(data: u64) -> Self {
+        let move_bits = data as u32;
+        return Self{
+            depth_left: (data >> DEPTH_LEFT_SHIFT) as u8,
+            evaluation: (data >> EVALUATION_SHIFT) as u16 as i16 as i32,
+            is_exact: data & IS_EXACT_MASK != 0,
+            is_alpha_cut: data & IS_ALPHA_CUT_MASK != 0,
+            is_beta_cut: data & IS_BETA_CUT_MASK != 0,
+            from_quiescence: data & FROM_QUIESCENCE_MASK != 0,
+            generation: (data >> GENERATION_SHIFT) as u8 & GENERATION_MASK,
+            maybe_pv_move: if move_bits == 0 {None} else {Some(Move::from_bits(move_bits))}
+        };
+    }
+}
+
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    return Ok(u32::from_le_bytes(bytes));
+}
+
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    return Ok(u64::from_le_bytes(bytes));
+}
+
+
+#[inline(always)]
+fn age(data: u64, generation: u8) -> u8 {
+    // how many searches ago the packed entry was stored
+    return generation.wrapping_sub((data >> GENERATION_SHIFT) as u8) & GENERATION_MASK;
+}
+
+
+#[inline(always)]
+fn depth(data: u64) -> i32 {
+    // how deep the packed entry was searched, where quiescence counts as depth 0
+    return if data & FROM_QUIESCENCE_MASK != 0 {0} else {1 + (data >> DEPTH_LEFT_SHIFT) as u8 as i32};
+}
+
+
+#[inline(always)]
+fn worth(data: u64, generation: u8) -> i32 {
+    // the deeper and the more recent, the more an entry is worth keeping
+    if data & IS_OCCUPIED_MASK == 0 {
+        return i32::MIN;
+    }
+    return depth(data) - AGE_PENALTY * age(data, generation) as i32;
 }
 
 
-enum EntryVariant<T> {
-    None,
-    FromAlphaBeta(T),
-    FromQuiescence(T)
+#[derive(Debug)]
+pub enum TranspositionTableFileError {
+    Io(io::Error),
+    NotATable,
+    UnsupportedVersion(u32),
+    IncompatibleLayout,
+    IncompatibleKeys
 }
 
 
+impl fmt::Display for TranspositionTableFileError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranspositionTableFileError::Io(error) => write!(f, "{error}"),
+            TranspositionTableFileError::NotATable => write!(f, "not a saved transposition table"),
+            TranspositionTableFileError::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            TranspositionTableFileError::IncompatibleLayout => write!(f, "entries are laid out differently"),
+            TranspositionTableFileError::IncompatibleKeys => write!(f, "saved with different zobrist keys"),
+        }
+    }
+}
+
+
+impl From<io::Error> for TranspositionTableFileError {
+    fn from(error: io::Error) -> Self {
+        TranspositionTableFileError::Io(error)
+    }
+}
+
+
+#[derive(Default)]
+struct AtomicEntry {
+    /*
+    Threads read and write the two words without a lock, so a reader may see the words of two
+    different writes. Storing the key as hash ^ data makes such a torn entry fail verification.
+    */
+    key: AtomicU64,
+    data: AtomicU64
+}
+
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket([AtomicEntry; BUCKET_SIZE]);
+
+
 pub struct TranspositionTable<Board: AlphaBetaSearchFunctionality> {
-    memory: Vec<EntryVariant<TranspositionTableEntry<Board>>>,
-    capacity: usize,
-    number_entries: usize
+    buckets: Vec<Bucket>,
+    capacity: usize,  // in entries
+    generation: AtomicU8,  // bumped for every search, to tell stale entries from recent ones
+    _phantom: PhantomData<fn() -> Board>
 }
 
 
 impl<Board: AlphaBetaSearchFunctionality> TranspositionTable<Board> {
 
-    const DEFAULT_CAPACITY: usize = 2 << 22;  // 2^22 ~ 4_000_000
+    pub const DEFAULT_SIZE_IN_MB: usize = 128;
+    pub const ENTRY_SIZE: usize = size_of::<Bucket>() / BUCKET_SIZE;  // in bytes
+    pub const MAX_SIZE_IN_MB: usize = usize::MAX >> 20;  // larger sizes don't fit into usize (on 32 bit targets)
 
     pub fn new() -> Self {
-        let mut memory = Vec::with_capacity(Self::DEFAULT_CAPACITY);
-        for _hash in 0..Self::DEFAULT_CAPACITY {
-            memory.push(EntryVariant::None)
-        }
-        return Self {memory, capacity: Self::DEFAULT_CAPACITY, number_entries: 0};
+        return Self::with_size_in_mb(Self::DEFAULT_SIZE_IN_MB);
+    }
+
+    pub fn with_size_in_mb(size_in_mb: usize) -> Self {
+        let mut transposition_table = Self{
+            buckets: Vec::new(),
+            capacity: 0,
+            generation: AtomicU8::new(0),
+            _phantom: PhantomData
+        };
+        transposition_table.set_size_in_mb(size_in_mb);
+        return transposition_table;
     }
 
     pub fn set_capacity_to(self: &mut Self, capacity: usize) {
-        // update the size of the transposition table
-
-        if self.capacity > capacity {
-            // take first N elements of memory
-            let diff = self.capacity - capacity;
-            self.capacity = capacity;
-            for _ in 0..diff {
-                self.memory.pop();
-            }
-        } else {
-            // add some empty elements to memory
-            let diff = capacity - self.capacity;
-            self.capacity = capacity;
-            for _ in 0..diff {
-                self.memory.push(EntryVariant::None);
+        // update the size of the transposition table (in entries, rounded up to full buckets), clearing it
+        let number_buckets = usize::max(1, capacity.div_ceil(BUCKET_SIZE));
+        self.buckets = Vec::new();  // free the old table before allocating the new one
+        self.buckets = (0..number_buckets).map(|_| Bucket::default()).collect();
+        self.capacity = number_buckets * BUCKET_SIZE;
+    }
+
+    pub fn set_size_in_mb(self: &mut Self, size_in_mb: usize) {
+        // update the size of the transposition table (in megabytes, at most MAX_SIZE_IN_MB), clearing it
+        self.set_capacity_to(size_in_mb.min(Self::MAX_SIZE_IN_MB) * (1 << 20) / Self::ENTRY_SIZE);
+    }
+
+    pub fn size_in_mb(self: &Self) -> usize {
+        // rounded up, so that even the smallest table takes (at least) a megabyte
+        return usize::max(1, (self.capacity * Self::ENTRY_SIZE).div_ceil(1 << 20));
+    }
+
+    pub fn save(self: &Self, path: impl AsRef<Path>) -> Result<(), TranspositionTableFileError> {
+        // write the table to a file, to be loaded again (by the same build) to resume an analysis
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&FILE_MAGIC)?;
+        writer.write_all(&FILE_VERSION.to_le_bytes())?;
+        writer.write_all(&(Self::ENTRY_SIZE as u32).to_le_bytes())?;
+        writer.write_all(&(BUCKET_SIZE as u32).to_le_bytes())?;
+        writer.write_all(&(self.generation.load(Ordering::Relaxed) as u32).to_le_bytes())?;
+        writer.write_all(&(self.capacity as u64).to_le_bytes())?;
+        writer.write_all(&Board::ZOBRIST_KEY_SET.to_le_bytes())?;
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.0) {
+            writer.write_all(&slot.key.load(Ordering::Relaxed).to_le_bytes())?;
+            writer.write_all(&slot.data.load(Ordering::Relaxed).to_le_bytes())?;
+        }
+        writer.flush()?;
+        return Ok(());
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TranspositionTableFileError> {
+        // read a table written by save, with the capacity it was saved with
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).map_err(|_| TranspositionTableFileError::NotATable)?;
+        if magic != FILE_MAGIC {
+            return Err(TranspositionTableFileError::NotATable);
+        }
+        let version = read_u32(&mut reader)?;
+        if version != FILE_VERSION {
+            return Err(TranspositionTableFileError::UnsupportedVersion(version));
+        }
+        let entry_size = read_u32(&mut reader)? as usize;
+        let bucket_size = read_u32(&mut reader)? as usize;
+        if entry_size != Self::ENTRY_SIZE || bucket_size != BUCKET_SIZE {
+            return Err(TranspositionTableFileError::IncompatibleLayout);
+        }
+        let generation = read_u32(&mut reader)? as u8;
+        let capacity = read_u64(&mut reader)?;
+        if capacity == 0 || capacity % BUCKET_SIZE as u64 != 0 {
+            return Err(TranspositionTableFileError::IncompatibleLayout);
+        }
+
+        // trust the capacity only as far as the file holds its entries, before allocating for them
+        let expected_file_size = capacity.checked_mul(FILE_ENTRY_SIZE)
+            .and_then(|entries_size| entries_size.checked_add(FILE_HEADER_SIZE));
+        if expected_file_size != Some(file_size) {
+            return Err(TranspositionTableFileError::NotATable);
+        }
+        let capacity = usize::try_from(capacity).map_err(|_| TranspositionTableFileError::IncompatibleLayout)?;
+        if read_u64(&mut reader)? != Board::ZOBRIST_KEY_SET {
+            return Err(TranspositionTableFileError::IncompatibleKeys);
+        }
+
+        let mut buckets: Vec<Bucket> = Vec::with_capacity(capacity / BUCKET_SIZE);
+        for _ in 0..capacity / BUCKET_SIZE {
+            let bucket = Bucket::default();
+            for slot in &bucket.0 {
+                slot.key.store(read_u64(&mut reader)?, Ordering::Relaxed);
+                slot.data.store(read_u64(&mut reader)?, Ordering::Relaxed);
             }
+            buckets.push(bucket);
+        }
+
+        return Ok(Self{
+            buckets,
+            capacity,
+            generation: AtomicU8::new(generation),
+            _phantom: PhantomData
+        });
+    }
+
+    pub fn clear(self: &Self) {
+        // empty all entries, only to be called while no search is running
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.0) {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
         }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    pub fn new_search(self: &Self) {
+        // entries stored from now on belong to a new search, the ones before age
+        self.generation.fetch_add(1, Ordering::Relaxed);
     }
 
     #[inline(always)]
-    fn index_from_hash(self: &Self, zobrist_hash: Board::ZobristHash) -> usize {
-        let hash_as_usize: usize = unsafe {
-            std::mem::transmute_copy(&zobrist_hash)
-        };  // TODO
-        hash_as_usize % self.capacity
+    fn bucket_from_hash(self: &Self, zobrist_hash: u64) -> &Bucket {
+        &self.buckets[(zobrist_hash % self.buckets.len() as u64) as usize]
+    }
+
+    fn probe(self: &Self, zobrist_hash: u64) -> Option<TranspositionTableEntry<Board::Move>> {
+        // the entry of the position, if any, which is refreshed to belong to the current search
+        let generation = self.generation.load(Ordering::Relaxed) & GENERATION_MASK;
+        for slot in &self.bucket_from_hash(zobrist_hash).0 {
+            let mut data = slot.data.load(Ordering::Relaxed);
+            if data & IS_OCCUPIED_MASK != 0 && slot.key.load(Ordering::Relaxed) ^ data == zobrist_hash {
+                if age(data, generation) > 0 {
+                    data = data & !((GENERATION_MASK as u64) << GENERATION_SHIFT) | (generation as u64) << GENERATION_SHIFT;
+                    Self::store(slot, zobrist_hash, data);
+                }
+                return Some(TranspositionTableEntry::unpack(data));
+            }
+        }
+        return None;
     }
 
     pub(crate) fn put<
         FromAlphaBeta: Bool,
         FromQuiescence: Bool
     >(
-        self: &mut Self, board: &Board,
+        self: &Self, board: &Board,
         depth_left: u8,
         distance_to_root: i32,
         mut evaluation: i32,
@@ -89,42 +348,60 @@ impl<Board: AlphaBetaSearchFunctionality> TranspositionTable<Board> {
     ) {
 
         // remove distance-to-root offset from mate score
+        // (fail-hard bounds may carry a mate score from closer to the root, hence clamp)
         if evaluation > MATE_EVALUATION / 2 {
-            evaluation += distance_to_root;
-            assert!(evaluation <= MATE_EVALUATION);
+            evaluation = i32::min(evaluation + distance_to_root, MATE_EVALUATION);
         } else if evaluation < -MATE_EVALUATION / 2 {
-            evaluation -= distance_to_root;
-            assert!(evaluation >= - MATE_EVALUATION);
+            evaluation = i32::max(evaluation - distance_to_root, -MATE_EVALUATION);
         }
 
-        // find index
-        let zobrist_hash = board.zobrist_hash();
-        let index = self.index_from_hash(zobrist_hash);
-
         // construct entry
+        let generation = self.generation.load(Ordering::Relaxed) & GENERATION_MASK;
         let entry = TranspositionTableEntry{
-            zobrist_hash,
             depth_left,
             evaluation,
             is_exact,
             is_alpha_cut,
             is_beta_cut,
+            from_quiescence: FromQuiescence::AS_BOOL && !FromAlphaBeta::AS_BOOL,
+            generation,
             maybe_pv_move,
         };
+        let data = entry.pack();
+
+        let zobrist_hash: u64 = board.zobrist_hash().into();
+        let bucket = self.bucket_from_hash(zobrist_hash);
 
-        // store entry
-        self.number_entries = usize::min(self.capacity, self.number_entries + 1);
-        if FromAlphaBeta::AS_BOOL {
-            self.memory[index] = EntryVariant::FromAlphaBeta(entry);
-        } else if FromQuiescence::AS_BOOL {
-            self.memory[index] = EntryVariant::FromQuiescence(entry);
+        // an entry of the same position is updated, unless it holds a (much) deeper result of this search
+        for slot in &bucket.0 {
+            let slot_data = slot.data.load(Ordering::Relaxed);
+            if slot_data & IS_OCCUPIED_MASK != 0 && slot.key.load(Ordering::Relaxed) ^ slot_data == zobrist_hash {
+                if is_exact || age(slot_data, generation) > 0 || depth(data) + 2 >= depth(slot_data) {
+                    Self::store(slot, zobrist_hash, data);
+                }
+                return;
+            }
         }
+
+        // else replace the least worthy depth-preferred entry, if worth less than the new one, or the always-replace entry
+        let (least_worthy, least_worth) = bucket.0[..DEPTH_PREFERRED].iter()
+            .map(|slot| (slot, worth(slot.data.load(Ordering::Relaxed), generation)))
+            .min_by_key(|&(_, slot_worth)| slot_worth)
+            .unwrap();
+        let slot = if least_worth <= worth(data, generation) {least_worthy} else {&bucket.0[BUCKET_SIZE - 1]};
+        Self::store(slot, zobrist_hash, data);
+    }
+
+    #[inline(always)]
+    fn store(slot: &AtomicEntry, zobrist_hash: u64, data: u64) {
+        slot.key.store(zobrist_hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
     }
 
     pub(crate) fn query<
         CalledInQuiescence: Bool
     >(
-        self: &mut Self,
+        self: &Self,
         board: &Board,
         mut alpha: i32,
         mut beta: i32,
@@ -138,147 +415,77 @@ impl<Board: AlphaBetaSearchFunctionality> TranspositionTable<Board> {
         let mut evaluation: i32 = I32_NAN;
         let mut maybe_pv_move: Option<Board::Move> = None;
 
-        let index = self.index_from_hash(board.zobrist_hash());
-        match &self.memory[index] {
-            EntryVariant::None => {},
-
-            EntryVariant::FromAlphaBeta(entry) => 'arm: {
-                if (CalledInQuiescence::AS_BOOL || (entry.depth_left >= depth_left)) &&
-                    entry.zobrist_hash == board.zobrist_hash() {
-
-                    // copy entry.evaluation
-                    let mut entry_evaluation = entry.evaluation;
-
-                    // add mate depth offset
-                    if entry_evaluation > MATE_EVALUATION/2 {
-                        entry_evaluation -= distance_to_root;
-                        assert!(entry_evaluation <= MATE_EVALUATION);
-                    } else if entry_evaluation < -MATE_EVALUATION/2 {
-                        entry_evaluation += distance_to_root;
-                        assert!(entry_evaluation >= -MATE_EVALUATION);
-                    }
-
-                    // check whether entry has an exact evaluation, if so return
-                    if entry.is_exact {
-                        is_hit = true;
-                        is_exact = true;
-                        evaluation = entry_evaluation;
-                        maybe_pv_move = entry.maybe_pv_move;
-                        break 'arm;
-                    }
-
-                    // update bounds
-                    if entry.is_alpha_cut {
-                        // entry_evaluation is an upper bound
-                        beta = i32::min(beta, entry_evaluation);
-                    } else if entry.is_beta_cut {
-                        // entry_evaluation is a lower bound
-                        alpha = i32::max(alpha, entry_evaluation);
-                    }
-
-                    // check for cut-off
-                    if alpha >= beta {
-                        is_hit = true;
-                        is_exact = false;
-                        evaluation = entry_evaluation;
-                        maybe_pv_move = entry.maybe_pv_move;
-                    }
-                };
-            },
-
-            EntryVariant::FromQuiescence(entry) => 'arm: {
-                if CalledInQuiescence::AS_BOOL && entry.zobrist_hash == board.zobrist_hash() {
-
-                    // copy entry.evaluation
-                    let mut entry_evaluation = entry.evaluation;
-
-                    // add mate depth offset
-                    if entry_evaluation > MATE_EVALUATION/2 {
-                        entry_evaluation -= distance_to_root;
-                        assert!(entry_evaluation <= MATE_EVALUATION);
-                    } else if entry_evaluation < -MATE_EVALUATION/2 {
-                        entry_evaluation += distance_to_root;
-                        assert!(entry_evaluation >= -MATE_EVALUATION);
-                    }
-
-                    // check whether entry has an exact evaluation, if so return
-                    if entry.is_exact {
-                        is_hit = true;
-                        is_exact = true;
-                        evaluation = entry_evaluation;
-                        maybe_pv_move = entry.maybe_pv_move;
-                        break 'arm;
-                    }
-
-                    // update bounds
-                    if entry.is_alpha_cut {
-                        // entry_evaluation is an upper bound
-                        beta = i32::min(beta, entry_evaluation);
-                    } else if entry.is_beta_cut {
-                        // entry_evaluation is a lower bound
-                        alpha = i32::max(alpha, entry_evaluation);
-                    }
-
-                    // check for cut-off
-                    if alpha >= beta {
-                        is_hit = true;
-                        is_exact = false;
-                        evaluation = entry_evaluation;
-                        maybe_pv_move = entry.maybe_pv_move;
-                    }
-                }
-            }
+        /*
+        alpha-beta entries are useful if searched at least as deep (or anywhere in quiescence),
+        quiescence entries only in quiescence
+        */
+        let Some(entry) = self.probe(board.zobrist_hash().into()) else {
+            return (is_hit, is_exact, evaluation, maybe_pv_move);
+        };
+        let is_usable = if entry.from_quiescence {
+            CalledInQuiescence::AS_BOOL
+        } else {
+            CalledInQuiescence::AS_BOOL || entry.depth_left >= depth_left
         };
 
-        return (is_hit, is_exact, evaluation, maybe_pv_move)
-    }
-
-    pub fn get_pv_line(self: &Self, board: &mut Board) -> Vec<Board::Move> {
-        // extract the pv line from the transposition table
+        'usable: {
+            if !is_usable {
+                break 'usable;
+            }
 
-        let mut n_moves_found: usize = 0;
-        let mut moves = Vec::with_capacity(16);
-        let mut seen_hashes = Vec::with_capacity(16);
+            // copy entry.evaluation
+            let mut entry_evaluation = entry.evaluation;
 
-        loop {
-            let current_hash = board.zobrist_hash();
-            let current_index = self.index_from_hash(current_hash);
+            // add mate depth offset
+            if entry_evaluation > MATE_EVALUATION/2 {
+                entry_evaluation -= distance_to_root;
+                assert!(entry_evaluation <= MATE_EVALUATION);
+            } else if entry_evaluation < -MATE_EVALUATION/2 {
+                entry_evaluation += distance_to_root;
+                assert!(entry_evaluation >= -MATE_EVALUATION);
+            }
 
-            // break loops
-            if seen_hashes.contains(&current_hash) {
-                println!("Loop in PV extraction from transposition table!");
-                break;
+            // check whether entry has an exact evaluation, if so return
+            if entry.is_exact {
+                is_hit = true;
+                is_exact = true;
+                evaluation = entry_evaluation;
+                maybe_pv_move = entry.maybe_pv_move;
+                break 'usable;
             }
 
-            match &self.memory[current_index] {
-                EntryVariant::None => break,
-                EntryVariant::FromQuiescence(_) => break, // TODO: We could extract moves here...
-                EntryVariant::FromAlphaBeta(entry) => {
-                    if entry.zobrist_hash == current_hash {
-                        match entry.maybe_pv_move {
-                            None => break,
-                            Some(r#move) => {
-                                n_moves_found += 1;
-                                moves.push(r#move);
-                                seen_hashes.push(current_hash);
-                                board.make_move(r#move);
-                            }
-                        }
-                    } else {
-                        break
-                    }
-                }
+            // update bounds
+            if entry.is_alpha_cut {
+                // entry_evaluation is an upper bound
+                beta = i32::min(beta, entry_evaluation);
+            } else if entry.is_beta_cut {
+                // entry_evaluation is a lower bound
+                alpha = i32::max(alpha, entry_evaluation);
             }
-        }
 
-        for _ in 0..n_moves_found {
-            board.unmake_move();
+            // check for cut-off
+            if alpha >= beta {
+                is_hit = true;
+                is_exact = false;
+                evaluation = entry_evaluation;
+                maybe_pv_move = entry.maybe_pv_move;
+            }
         }
 
-        return moves;
+        return (is_hit, is_exact, evaluation, maybe_pv_move)
     }
 
     pub fn fill_level_per_mill(self: &Self) -> usize {
-        (1000. * (self.number_entries as f32) / (self.capacity as f32)) as usize
+        // estimated from the first entries (positions are spread evenly), only counting this search's
+        let generation = self.generation.load(Ordering::Relaxed);
+        let (mut n_sampled, mut n_filled) = (0, 0);
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.0).take(HASHFULL_SAMPLE) {
+            let data = slot.data.load(Ordering::Relaxed);
+            n_sampled += 1;
+            if data & IS_OCCUPIED_MASK != 0 && age(data, generation) == 0 {
+                n_filled += 1;
+            }
+        }
+        return 1000 * n_filled / n_sampled;
     }
 }
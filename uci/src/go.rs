@@ -1,16 +1,34 @@
-use std::ops::DerefMut;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::cell::Cell;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use search::{clear_stop, emit_stop, query_stop, I32_NAN};
-use search::alpha_beta::alpha_beta;
+use search::{clear_stop, emit_stop, query_stop};
+use search::alpha_beta::{alpha_beta, alpha_beta_multi_pv, alpha_beta_with_aspiration, mate_search};
+use search::parameters::SearchParameters;
+use search::search_info::{Bound, SearchInfo};
 use search::traits::AlphaBetaSearchFunctionality;
 use search::transposition_table::TranspositionTable;
 use crate::parsing::{bestmove, info};
 
-pub struct GoInfo<Board> {
-    _phantom: std::marker::PhantomData<Board>,  // placeholder for "searchmoves: Vec<Board::Move>"
+#[derive(Copy, Clone)]
+enum Mode {
+    BestMove,  // one line, in aspiration windows
+    MultiPv(usize),  // the best lines, each with a full window
+    Mate(u8)  // a mate in this many moves
+}
+
+
+struct Task<'m, Move> {
+    mode: Mode,
+    search_moves: &'m [Move]  // the only root moves to search, all if empty
+}
+
+
+pub struct GoInfo<Board: AlphaBetaSearchFunctionality> {
+    pub searchmoves: Vec<Board::Move>,  // restrict the search to these root moves (all if empty)
 
     pub whites_turn: bool,
 
@@ -33,12 +51,15 @@ pub struct GoInfo<Board> {
 
     pub depth_given: bool,
     pub depth: usize,  // search until this depth
+
+    pub mate_given: bool,
+    pub mate: usize,  // search for a mate in this many moves
 }
 
-impl<Board> Default for GoInfo<Board> {
+impl<Board: AlphaBetaSearchFunctionality> Default for GoInfo<Board> {
     fn default() -> Self {
         Self{
-            _phantom: std::marker::PhantomData,
+            searchmoves: Vec::new(),
             whites_turn: false,
             wtime_given: false,
             wtime: 0,
@@ -55,12 +76,14 @@ impl<Board> Default for GoInfo<Board> {
             movetime: 0,
             depth_given: false,
             depth: 0,
+            mate_given: false,
+            mate: 0,
         }
     }
 }
 
 impl<Board> GoInfo<Board> where
-    Board: AlphaBetaSearchFunctionality + Send + 'static,
+    Board: AlphaBetaSearchFunctionality + Clone + Send + 'static,
     Board::ZobristHash: Send + Sync,
     Board::Move: Send + Sync
 {
@@ -80,12 +103,18 @@ impl<Board> GoInfo<Board> where
     pub fn search(
         self: &Self,
         mut board: Board,
-        transposition_table_arc_mutex: Arc<Mutex<TranspositionTable<Board>>>
+        parameters: SearchParameters,
+        threads: usize,
+        multi_pv: usize,
+        transposition_table: Arc<TranspositionTable<Board>>
     ) {
 
         // clear old stop signal
         clear_stop();
 
+        // entries of earlier searches become stale
+        transposition_table.new_search();
+
         // decide whether search is times or a max depth is given
         let timed: bool = self.movestogo_given
             ||self.movetime_given
@@ -94,7 +123,18 @@ impl<Board> GoInfo<Board> where
             ||self.winc_given
             ||self.binc_given;
         let max_depth_given: bool = self.depth_given;
-        let max_depth: u8 = if max_depth_given {self.depth as u8} else {u8::MAX};
+        let mut max_depth: u8 = if max_depth_given {self.depth as u8} else {u8::MAX};
+
+        // a mate in n moves is at most 2n - 1 plies away, and detected one ply later (quiescence doesn't)
+        let mode = if self.mate_given {
+            let mate_in = self.mate.clamp(1, 127) as u8;
+            max_depth = max_depth.min(2 * mate_in);
+            Mode::Mate(mate_in)
+        } else if multi_pv > 1 {
+            Mode::MultiPv(multi_pv)
+        } else {
+            Mode::BestMove
+        };
 
         // maybe time the search
         if timed {
@@ -113,61 +153,89 @@ impl<Board> GoInfo<Board> where
         }
 
 
-        // search!
-        thread::spawn(move || {
-
-            // access (mutable) reference to transposition table
-            let mut guard = transposition_table_arc_mutex
-                .lock().expect("Couldn't access transposition table in search thread!");
-            let transposition_table = guard.deref_mut();
+        // helper threads search the same root and fill the shared transposition table (lazy SMP)
+        let helper_nodes = Arc::new(AtomicUsize::new(0));
+        let helpers: Vec<JoinHandle<()>> = (1..threads).map(|index| {
+            let mut board = board.clone();
+            let parameters = parameters.clone();
+            let transposition_table = transposition_table.clone();
+            let helper_nodes = helper_nodes.clone();
+            let search_moves = self.searchmoves.clone();
+            thread::spawn(move || {
+                // half of the helpers start one ply deeper, so that the threads diverge
+                let first_depth = u8::min(1 + (index % 2) as u8, max_depth);
+                iterative_deepening(
+                    &mut board, first_depth..=max_depth, Task{mode, search_moves: &search_moves},
+                    &parameters, &transposition_table,
+                    |info, _| {helper_nodes.fetch_add(info.nodes_visited, Ordering::Relaxed);},
+                    |_, _| {}
+                );
+            })
+        }).collect();
 
-            // do search
-            let mut current_max_depth: u8 = 1;
+        // search! (the main thread alone reports and decides on the best move)
+        let whites_turn = board.is_whites_turn();
+        let search_moves = self.searchmoves.clone();
+        thread::spawn(move || {
+            let now = Instant::now();
+            let main_nodes: Cell<usize> = Cell::new(0);
             let mut maybe_best_move: Option<Board::Move> = None;
-            let mut _maybe_evaluation: i32 = I32_NAN;
-            loop {  // iterative deepening
 
-                // do search to current depth
-                let current_search_info = alpha_beta(
-                    &mut board, current_max_depth, transposition_table
-                );
+            // every line with nodes and time of all threads since "go"
+            let report = |current_search_info: &SearchInfo<Board>, depth: u8, nodes_this_depth: usize| {
+                let time_in_ms = now.elapsed().as_millis();
+                let nodes = main_nodes.get() + nodes_this_depth + helper_nodes.load(Ordering::Relaxed);
+                let hashfull_per_mill = current_search_info.transposition_table.fill_level_per_mill();
 
-                // break if stop signal was received and alpha_beta returned early
-                if query_stop() {
-                    println!("Terminated search to depth {current_max_depth}");
-                    break;
+                for (index, line) in current_search_info.root_moves.iter().enumerate() {
+                    // the score from the side to move's point of view, none while a mate search hasn't proven the mate
+                    let score = match mode {
+                        Mode::Mate(_) if line.bound != Bound::Exact => None,
+                        _ if whites_turn => Some((line.evaluation, line.bound)),
+                        _ => Some((-line.evaluation, line.bound.flipped()))
+                    };
+                    info::<Board::Move>(
+                        Some(depth),
+                        if let Mode::MultiPv(_) = mode {Some(index + 1)} else {None},
+                        Some(time_in_ms),
+                        Some(nodes),
+                        Some(line.principal_variation.clone()),
+                        score,
+                        Some(hashfull_per_mill),
+                    );
                 }
+            };
 
-                // visualize results
-                // current_search_info.visualize();
-                let depth = current_max_depth;
-                let time_in_ms = current_search_info.time_spent_searching;
-                let nodes = current_search_info.nodes_visited;
-                let pv_line = current_search_info.transposition_table.get_pv_line(&mut board);
-                let score = current_search_info.evaluation;
-                let hashfull_per_mill = current_search_info.transposition_table.fill_level_per_mill();
-                let nps = (1000. * (current_search_info.nodes_visited as f32) / (current_search_info.time_spent_searching as f32)) as usize;
-                info::<Board::Move>(
-                    Some(depth),
-                    Some(time_in_ms),
-                    Some(nodes),
-                    Some(pv_line),
-                    Some(score),
-                    Some(hashfull_per_mill),
-                    Some(nps),
-                );
+            iterative_deepening(
+                &mut board, 1..=max_depth, Task{mode, search_moves: &search_moves}, &parameters, &transposition_table,
+                |current_search_info, depth| {
+                    main_nodes.set(main_nodes.get() + current_search_info.nodes_visited);
+
+                    // results of an iteration interrupted by the stop signal are incomplete
+                    if query_stop() {
+                        println!("Terminated search to depth {depth}");
+                        return;
+                    }
 
-                current_max_depth += 1;
-                _maybe_evaluation = current_search_info.evaluation;
-                maybe_best_move = current_search_info.best_move;
+                    // visualize results
+                    // current_search_info.visualize();
+                    report(current_search_info, depth, 0);
 
-                // break if search of final depth is done
-                if max_depth_given {
-                    if current_max_depth > max_depth {
-                        break;
+                    maybe_best_move = current_search_info.best_move;
+                },
+                |failed_search_info, depth| {
+                    // the evaluation of a failed aspiration window is a bound
+                    if !query_stop() {
+                        report(failed_search_info, depth, failed_search_info.nodes_visited);
                     }
                 }
-            };
+            );
+
+            // the main thread is done, so the helpers are too
+            emit_stop();
+            for helper in helpers {
+                helper.join().expect("Helper search thread panicked!");
+            }
 
             // echo bestmove and possibly information (TODO)
             match maybe_best_move {
@@ -182,3 +250,48 @@ impl<Board> GoInfo<Board> where
         });
     }
 }
+
+
+
+fn iterative_deepening<Board: AlphaBetaSearchFunctionality>(
+    board: &mut Board,
+    depths: RangeInclusive<u8>,
+    task: Task<Board::Move>,
+    parameters: &SearchParameters,
+    transposition_table: &TranspositionTable<Board>,
+    mut on_iteration: impl FnMut(&SearchInfo<Board>, u8),
+    mut on_window_fail: impl FnMut(&SearchInfo<Board>, u8)
+) {
+    /*
+    search ever deeper until the last depth or the stop signal, reporting every (even interrupted)
+    iteration and every failed aspiration window. Looking for a mate, stop once it is proven
+    */
+
+    let Task{mode, search_moves} = task;
+    let mut depth = *depths.start();
+    let mut maybe_evaluation: Option<i32> = None;
+    loop {
+        // do search to current depth, in a window around the evaluation of the previous depth
+        let current_search_info = match (mode, maybe_evaluation) {
+            (Mode::Mate(mate_in), _) => mate_search(
+                board, depth, mate_in, search_moves, parameters, transposition_table
+            ),
+            (Mode::MultiPv(n_lines), _) => alpha_beta_multi_pv(
+                board, depth, n_lines, search_moves, parameters, transposition_table
+            ),
+            (Mode::BestMove, None) => alpha_beta(board, depth, search_moves, parameters, transposition_table),
+            (Mode::BestMove, Some(evaluation)) => alpha_beta_with_aspiration(
+                board, depth, evaluation, search_moves, parameters, transposition_table,
+                |info| on_window_fail(info, depth)
+            )
+        };
+        on_iteration(&current_search_info, depth);
+
+        let is_mate_proven = matches!(mode, Mode::Mate(_)) && current_search_info.bound == Bound::Exact;
+        if query_stop() || is_mate_proven || depth >= *depths.end() {
+            break;
+        }
+        maybe_evaluation = Some(current_search_info.evaluation);
+        depth += 1;
+    }
+}
@@ -1,17 +1,28 @@
 
 /*
 TODO:
-    - move perft to separate module
     - transposition table via pointers? I don't like the TT in the search info and all of the lifetime stuff
     - search doesn't return mate
 */
 
 use crate::parsing::uci_loop;
+use crate::perft::run_suite;
 
 mod parsing;
 mod go;
+mod options;
+mod perft;
 
 
 fn main() {
+    // "uci perft <suite.epd> [<max depth>]" checks move generation and exits, anything else starts the UCI loop
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    if arguments.first().is_some_and(|argument| argument == "perft") {
+        let path = arguments.get(1).expect("usage: uci perft <suite.epd> [<max depth>]");
+        let max_depth = arguments.get(2).map(|depth| depth.parse().expect("Invalid max depth!"));
+        let all_match = run_suite(path, max_depth);
+        std::process::exit(if all_match {0} else {1});
+    }
+
     uci_loop();
 }
\ No newline at end of file
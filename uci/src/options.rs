@@ -0,0 +1,136 @@
+/*
+Settings the GUI can change via "setoption", announced via "option" after "uci".
+
+TODO:
+    - warn if the hash doesn't fit into memory
+*/
+
+use std::sync::Arc;
+
+use board::board::Board;
+use search::parameters::SearchParameters;
+use search::transposition_table::TranspositionTable;
+
+
+const MAX_THREADS: i64 = 256;
+const MAX_MULTI_PV: i64 = 256;
+const MAX_HASH_IN_MB: i64 = {
+    // 64 GB, or what a 32 bit target can address
+    let max_size_in_mb = TranspositionTable::<Board>::MAX_SIZE_IN_MB;
+    if max_size_in_mb < 1 << 16 {max_size_in_mb as i64} else {1 << 16}
+};
+const DEFAULT_HASH_FILE: &str = "hash.tt";
+
+
+pub struct UciOptions {
+    pub chess960: bool,  // "UCI_Chess960": castling is sent and expected as king takes rook
+    pub threads: usize,  // "Threads": the number of threads searching in parallel (lazy SMP)
+    pub multi_pv: usize,  // "MultiPV": the number of best moves to search and report lines for
+    pub hash_in_mb: usize,  // "Hash": the size of the transposition table
+    pub hash_file: String,  // "Hash File": where "Save Hash" and "Load Hash" write and read the transposition table
+    pub search_parameters: SearchParameters  // "LMR_Base", "LMR_Divisor", "LMP_Depth", "LMP_MoveCount"
+}
+
+
+impl Default for UciOptions {
+    fn default() -> Self {
+        Self{
+            chess960: false,
+            threads: 1,
+            multi_pv: 1,
+            hash_in_mb: TranspositionTable::<Board>::DEFAULT_SIZE_IN_MB,
+            hash_file: DEFAULT_HASH_FILE.to_owned(),
+            search_parameters: SearchParameters::default()
+        }
+    }
+}
+
+
+impl UciOptions {
+    pub fn announce(self: &Self) {
+        // the "option" lines sent after "uci"
+        let defaults = SearchParameters::default();
+        println!("option name UCI_Chess960 type check default false");
+        println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
+        println!("option name MultiPV type spin default 1 min 1 max {MAX_MULTI_PV}");
+        println!(
+            "option name Hash type spin default {} min 1 max {MAX_HASH_IN_MB}",
+            TranspositionTable::<Board>::DEFAULT_SIZE_IN_MB
+        );
+        println!("option name Clear Hash type button");
+        println!("option name Hash File type string default {DEFAULT_HASH_FILE}");
+        println!("option name Save Hash type button");
+        println!("option name Load Hash type button");
+        println!("option name LMR_Base type spin default {} min 0 max 500", defaults.reduction_base);
+        println!("option name LMR_Divisor type spin default {} min 1 max 1000", defaults.reduction_divisor);
+        println!("option name LMP_Depth type spin default {} min 0 max 16", defaults.pruning_depth);
+        println!("option name LMP_MoveCount type spin default {} min 0 max 64", defaults.pruning_move_count);
+    }
+
+    pub fn set(
+        self: &mut Self,
+        name: &str,
+        value: Option<&str>,
+        transposition_table: &mut Arc<TranspositionTable<Board>>
+    ) -> Result<(), String> {
+        // apply "setoption name <name> [value <value>]" (option names are case-insensitive)
+        let parameters = &mut self.search_parameters;
+        match name.to_ascii_lowercase().as_str() {
+            "uci_chess960" => self.chess960 = parse_check(name, value)?,
+            "threads" => self.threads = parse_spin(name, value, 1, MAX_THREADS)? as usize,
+            "multipv" => self.multi_pv = parse_spin(name, value, 1, MAX_MULTI_PV)? as usize,
+            "hash" => {
+                self.hash_in_mb = parse_spin(name, value, 1, MAX_HASH_IN_MB)? as usize;
+                match Arc::get_mut(transposition_table) {
+                    Some(transposition_table) => transposition_table.set_size_in_mb(self.hash_in_mb),
+
+                    // a finishing search still holds the table, it's freed once that search lets go
+                    None => *transposition_table = Arc::new(TranspositionTable::with_size_in_mb(self.hash_in_mb))
+                }
+            },
+            "clear hash" => transposition_table.clear(),
+            "hash file" => match value {
+                Some(path) if !path.is_empty() => self.hash_file = path.to_owned(),
+                _ => return Err(format!("option \"{name}\" expects a path"))
+            },
+            "save hash" => transposition_table.save(&self.hash_file)
+                .map_err(|error| format!("saving hash to \"{}\" failed: {error}", self.hash_file))?,
+            "load hash" => {
+                // the loaded table keeps the size it was saved with
+                let loaded = TranspositionTable::load(&self.hash_file)
+                    .map_err(|error| format!("loading hash from \"{}\" failed: {error}", self.hash_file))?;
+                self.hash_in_mb = loaded.size_in_mb().min(MAX_HASH_IN_MB as usize);
+                *transposition_table = Arc::new(loaded);
+            },
+            "lmr_base" => {
+                parameters.reduction_base = parse_spin(name, value, 0, 500)? as u32;
+                parameters.fill_reductions();
+            },
+            "lmr_divisor" => {
+                parameters.reduction_divisor = parse_spin(name, value, 1, 1000)? as u32;
+                parameters.fill_reductions();
+            },
+            "lmp_depth" => parameters.pruning_depth = parse_spin(name, value, 0, 16)? as u8,
+            "lmp_movecount" => parameters.pruning_move_count = parse_spin(name, value, 0, 64)? as usize,
+            _ => return Err(format!("unknown option \"{name}\""))
+        }
+        return Ok(());
+    }
+}
+
+
+fn parse_check(name: &str, value: Option<&str>) -> Result<bool, String> {
+    match value {
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        _ => Err(format!("option \"{name}\" expects \"true\" or \"false\""))
+    }
+}
+
+
+fn parse_spin(name: &str, value: Option<&str>, min: i64, max: i64) -> Result<i64, String> {
+    match value.and_then(|value| value.parse::<i64>().ok()) {
+        Some(number) if min <= number && number <= max => Ok(number),
+        _ => Err(format!("option \"{name}\" expects an integer from {min} to {max}"))
+    }
+}
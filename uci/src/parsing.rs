@@ -1,12 +1,15 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use board::board::Board;
 use board::moves::Move;
 
-use search::emit_stop;
+use search::{emit_stop, MATE_EVALUATION};
+use search::search_info::Bound;
 use search::traits::SearchableMove;
 use search::transposition_table::TranspositionTable;
 
 use crate::go::GoInfo;
+use crate::options::UciOptions;
+use crate::perft::{divide, run_suite};
 
 // const NAME: &'static str = "|אֶמֶת|";
 const NAME: &'static str = "|אמת|";
@@ -26,17 +29,26 @@ fn prefix_until_whitespace(s: &str) -> &str {
 }
 
 
+fn is_go_subcommand(s: &str) -> bool {
+    matches!(
+        s,
+        "searchmoves"|"ponder"|"wtime"|"btime"|"winc"|"binc"|"movestogo"|"depth"|"nodes"|"mate"|"movetime"|"infinite"
+    )
+}
+
+
 pub fn parse_command(
     command: String,
     board: &mut Board,
-    transposition_table_arc_mutex: Arc<Mutex<TranspositionTable<Board>>>
+    options: &mut UciOptions,
+    transposition_table: &mut Arc<TranspositionTable<Board>>
 ) {
     // https://page.mi.fu-berlin.de/block/uci.htm
 
     // trim whitespaces, tabs, linebreaks, ... from both sides
     let command = command.trim();
 
-    if command.starts_with("uci") {
+    if prefix_until_whitespace(command) == "uci" {
         /*
         1. identify via "id" command
         2. reveal changeable settings via "option" command
@@ -47,7 +59,7 @@ pub fn parse_command(
         id();
 
         // 2.
-        option();
+        options.announce();
 
         // 3.
         uciok();
@@ -71,7 +83,24 @@ pub fn parse_command(
     }
 
     if command.starts_with("setoption") {
-        todo!();
+        // "setoption name <name> [value <value>]", where the name may contain spaces
+        let content = command.strip_prefix("setoption").unwrap().trim();
+        let Some(content) = content.strip_prefix("name") else {
+            info_string("\"setoption\" needs a name");
+            return;
+        };
+        let (name, value) = match content.find(" value ") {
+            None => (content.trim(), None),
+            Some(i) => (content[..i].trim(), Some(content[i + " value ".len()..].trim()))
+        };
+        if let Err(error) = options.set(name, value, transposition_table) {
+            info_string(&error);
+        }
+    }
+
+    if command.starts_with("ucinewgame") {
+        // nothing learned in the previous game applies (the history heuristic starts empty with every search anyway)
+        transposition_table.clear();
     }
 
     if command.starts_with("position") {
@@ -90,7 +119,14 @@ pub fn parse_command(
             };
             content = content.strip_prefix(fen).unwrap();
             content = remove_whitespace_prefix(content);
-            *board = Board::from_fen(fen);
+            match Board::try_from_fen(fen) {
+                Ok(new_board) => *board = new_board,
+                Err(error) => {
+                    // keep the previous position and tell the GUI why
+                    info_string(&format!("invalid FEN \"{}\": {error}", fen.trim()));
+                    return;
+                }
+            }
 
         } else if content.starts_with("startpos") {
             content = content.strip_prefix("startpos").unwrap();
@@ -103,6 +139,9 @@ pub fn parse_command(
             panic!("Invalid \"position\" command!")
         }
 
+        // with UCI_Chess960 castling is written as king takes rook, even from the standard position
+        board.chess960 |= options.chess960;
+
         // parse given moves (if any)
         if content.starts_with("moves") {
             content = content.strip_prefix("moves").unwrap();
@@ -125,8 +164,7 @@ pub fn parse_command(
         let mut content = command.strip_prefix("go").unwrap();
         content = remove_whitespace_prefix(content);
 
-        let mut go_info = GoInfo::<Board>::default();
-        go_info.whites_turn = board.whites_turn;
+        let mut go_info = GoInfo::<Board>{whites_turn: board.whites_turn, ..Default::default()};
 
         while content.len() > 0 {
             // extract subcommand
@@ -136,7 +174,7 @@ pub fn parse_command(
 
             // parse subcommand
             match subcommand {
-                "wtime"|"btime"|"winc"|"binc"|"movestogo"|"depth"|"movetime" => {
+                "wtime"|"btime"|"winc"|"binc"|"movestogo"|"depth"|"movetime"|"mate" => {
 
                     // parse given number
                     let number_str = prefix_until_whitespace(content);
@@ -155,11 +193,26 @@ pub fn parse_command(
                         "movestogo" => {go_info.movestogo = number; go_info.movestogo_given = true},
                         "depth"     => {go_info.depth     = number; go_info.depth_given     = true},
                         "movetime"  => {go_info.movetime  = number; go_info.movetime_given  = true},
+                        "mate"      => {go_info.mate      = number; go_info.mate_given      = true},
                         _ => unreachable!()
                     }
                 },
                 "infinite" => { go_info.infinite = true;}
-                "ponder"|"nodes"|"mate"|"searchmoves" => {unimplemented!()},  // TODO
+                "searchmoves" => {
+                    // the moves run up to the next subcommand, only legal ones are kept
+                    let legal_moves = board.get_legal_moves();
+                    while content.len() > 0 && !is_go_subcommand(prefix_until_whitespace(content)) {
+                        let move_str = prefix_until_whitespace(content);
+                        content = content.strip_prefix(move_str).unwrap();
+                        content = remove_whitespace_prefix(content);
+
+                        match legal_moves.iter().find(|r#move| r#move.to_string() == move_str) {
+                            Some(r#move) => go_info.searchmoves.push(*r#move),
+                            None => info_string(&format!("ignoring illegal move \"{move_str}\" of \"searchmoves\""))
+                        }
+                    }
+                },
+                "ponder"|"nodes" => {unimplemented!()},  // TODO
                 _ => {
                     println!("Unknown subcommand \"{}\" of \"go\" command!", subcommand);
                     break 'block;
@@ -169,10 +222,27 @@ pub fn parse_command(
 
         // search
         let clone = board.clone();  // TODO: If I trust make/unmake this should be unnecessary
-        go_info.search(clone, transposition_table_arc_mutex);
+        go_info.search(
+            clone, options.search_parameters.clone(), options.threads, options.multi_pv, transposition_table.clone()
+        );
 
     }}
 
+    if command.starts_with("perft") {
+        // "perft <depth>" divides the current position, "perft <suite.epd> [<max depth>]" runs a suite
+        let mut arguments = command.split_whitespace().skip(1);
+        match arguments.next() {
+            None => info_string("\"perft\" needs a depth or a suite"),
+            Some(argument) => match argument.parse::<u8>() {
+                Ok(depth) => divide(board, depth),
+                Err(_) => {
+                    let max_depth = arguments.next().and_then(|depth| depth.parse().ok());
+                    run_suite(argument, max_depth);
+                }
+            }
+        }
+    }
+
     if command.starts_with("stop") {
         emit_stop();
         println!()
@@ -210,16 +280,16 @@ fn readyok() {
 
 pub fn info<Move: SearchableMove>(
     depth: Option<u8>,
+    multipv: Option<usize>,
     time_in_ms: Option<u128>,
     nodes: Option<usize>,
     pv: Option<Vec<Move>>,
-    score: Option<i32>,
-    hashfull_in_per_mill: Option<usize>,
-    nps: Option<usize>
+    score: Option<(i32, Bound)>,  // from the engine's point of view
+    hashfull_in_per_mill: Option<usize>
 ) {
     /*
     TODO:
-        - seldepth, multipv, score.cp/mate/lowerbound/upperbound, currmove, currmovenumber, tbhits, cpuload, string
+        - seldepth, currmove, currmovenumber, tbhits, cpuload
     */
 
     let mut info: String = "info".to_owned();
@@ -229,9 +299,27 @@ pub fn info<Move: SearchableMove>(
         Some(d) => info.push_str(&format!(" depth {d}")),
     }
 
+    match multipv {
+        None => {},
+        Some(k) => info.push_str(&format!(" multipv {k}")),
+    }
+
     match score {
         None => {},
-        Some(s) => info.push_str(&format!(" score cp {s}")),
+        Some((s, bound)) => {
+            // mate scores count the plies to the mate, UCI counts moves (negative if getting mated)
+            if s.abs() > MATE_EVALUATION / 2 {
+                let n_moves = (MATE_EVALUATION - s.abs() + 1) / 2;
+                info.push_str(&format!(" score mate {}", s.signum() * n_moves));
+            } else {
+                info.push_str(&format!(" score cp {s}"));
+            }
+            match bound {
+                Bound::Exact => {},
+                Bound::Lower => info.push_str(" lowerbound"),
+                Bound::Upper => info.push_str(" upperbound"),
+            }
+        },
     }
 
     match time_in_ms {
@@ -244,9 +332,9 @@ pub fn info<Move: SearchableMove>(
         Some(n) => info.push_str(&format!(" nodes {n}")),
     }
 
-    match nps {
-        None => {},
-        Some(n) => info.push_str(&format!(" nps {n}")),
+    // nodes per second follow from the two above
+    if let (Some(t), Some(n)) = (time_in_ms, nodes) {
+        info.push_str(&format!(" nps {}", (1000. * (n as f32) / (t.max(1) as f32)) as usize));
     }
 
     match pv {
@@ -270,6 +358,11 @@ pub fn info<Move: SearchableMove>(
 }
 
 
+pub fn info_string(message: &str) {
+    println!("info string {message}");
+}
+
+
 pub fn bestmove<Move: SearchableMove>(r#move: Move, ponder: Option<Move>) {
     print!("bestmove {}", r#move.to_string());
     match ponder {
@@ -280,21 +373,16 @@ pub fn bestmove<Move: SearchableMove>(r#move: Move, ponder: Option<Move>) {
 }
 
 
-fn option() {
-    // TODO
-}
-
-
 pub fn uci_loop() {
 
-    let transposition_table: TranspositionTable<Board> = TranspositionTable::new();
-    let tt_arc_mutex = Arc::new(Mutex::new(transposition_table));
+    let mut transposition_table: Arc<TranspositionTable<Board>> = Arc::new(TranspositionTable::new());
 
     let mut board: Board = Board::default();
+    let mut options = UciOptions::default();
 
     loop {
         let mut command: String = String::new();
         std::io::stdin().read_line(&mut command).expect("Line parsing panic-ed!");
-        parse_command(command, &mut board, tt_arc_mutex.clone());
+        parse_command(command, &mut board, &mut options, &mut transposition_table);
     }
 }
\ No newline at end of file
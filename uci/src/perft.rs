@@ -0,0 +1,78 @@
+/*
+Perft from the UCI loop ("perft <depth>", "perft <suite.epd> [<max depth>]") and the command line
+("uci perft <suite.epd> [<max depth>]"), to catch move generation regressions outside of the tests.
+*/
+
+use std::time::Instant;
+
+use board::board::Board;
+use board::perft::PerftEntry;
+
+
+pub fn divide(board: &mut Board, depth: u8) {
+    // node counts per root move and in total, as most engines print them for comparison
+
+    let now = Instant::now();
+    let divide = board.perft_divide(depth);
+    let duration = now.elapsed();
+
+    let mut nodes = 0;
+    for (r#move, count) in divide {
+        println!("{}: {count}", r#move.to_string());
+        nodes += count;
+    }
+    println!();
+    println!("Nodes searched: {nodes}");
+    println!("Time: {}ms", duration.as_millis());
+    println!();
+}
+
+
+pub fn run_suite(path: &str, max_depth: Option<u8>) -> bool {
+    // check every position of the suite against its expected node counts, returns whether all matched
+
+    let suite = match std::fs::read_to_string(path) {
+        Ok(suite) => suite,
+        Err(error) => {
+            println!("Couldn't read perft suite \"{path}\": {error}");
+            return false;
+        }
+    };
+    let entries = match PerftEntry::read_all(&suite) {
+        Ok(entries) => entries,
+        Err(error) => {
+            println!("Invalid perft suite \"{path}\": {error}");
+            return false;
+        }
+    };
+
+    let now = Instant::now();
+    let mut checks = 0;
+    let mut mismatches = 0;
+    for (index, entry) in entries.iter().enumerate() {
+        let mut board = entry.board.clone();
+        println!("#{} {}", index + 1, board.to_fen());
+
+        for &(depth, expected) in &entry.node_counts {
+            if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                continue;
+            }
+            let nodes = board.perft(depth);
+            checks += 1;
+            if nodes == expected {
+                println!("    depth {depth}: {nodes} ok");
+            } else {
+                mismatches += 1;
+                println!("    depth {depth}: {nodes}, expected {expected} MISMATCH");
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "perft suite: {} of {checks} node counts match, {mismatches} mismatches ({:.1}s)",
+        checks - mismatches,
+        now.elapsed().as_secs_f32()
+    );
+    return mismatches == 0;
+}